@@ -43,6 +43,11 @@ fn main() {
     // Create the parsing engine
     let mut engine = csgo::core::Engine::new(cfg);
 
-    // Start watching for logs and parsing
-    engine.run();
+    if args.batch {
+        // Parse the logs that are already there, then stop
+        engine.run_batch();
+    } else {
+        // Start watching for logs and parsing
+        engine.run();
+    }
 }
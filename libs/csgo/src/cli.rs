@@ -14,6 +14,10 @@ pub struct Opt {
     #[structopt(name = "DIR", parse(from_os_str))]
     pub log_directory: PathBuf,
 
+    /// Parse all the logs in the directory once and report on how many lines were understood, rather than watching for new lines
+    #[structopt(short, long)]
+    pub batch: bool,
+
     /// Output format to generate (json, xml, csv, etc.)
     #[structopt(short, long, default_value = "json")]
     pub generate: String,
@@ -1,7 +1,10 @@
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Write;
 
+use super::state;
+
 const DEFAULT_CONFIG: &str = r#"
 # The directory containing your CS:GO server game logs, e.g. <server install dir>/server/logs
 log_dir = ''
@@ -9,6 +12,32 @@ log_dir = ''
 # How many seconds to wait before checking for new logs
 delay = 2
 
+# The timezone the server writes its log timestamps in, e.g. 'Europe/London' (see https://en.wikipedia.org/wiki/List_of_tz_database_time_zones)
+# Defaults to UTC if not set
+#timezone = 'UTC'
+
+# Whether events involving bots should be included in the stats
+# Bots don't have a Steam ID, so they are given a synthetic one based on their name, e.g. 'BOT_Brett'
+include_bots = true
+
+# How many seconds a teammate has to kill the killer for a death to count as traded
+#trade_window = 5
+
+# Whether lines that the parser doesn't understand should be emitted as 'unparsed' events
+emit_unparsed = false
+
+# Optionally, declare extra patterns for log lines the parser doesn't know about, e.g. ones written by SourceMod plugins
+# Each line matching a pattern becomes an event with the pattern's name as its event_type
+# Named capture groups become fields of the same name, and [custom_patterns.fields] maps numbered capture groups to field names
+# If the pattern has a capture group for the log timestamp, say which one it is with 'timestamp'
+#[[custom_patterns]]
+#name = 'clutch_won'
+#pattern = '^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "(.+)<\d+><([^<>]+)><[^<>]*>" triggered "clutch_won"'
+#timestamp = 1
+#[custom_patterns.fields]
+#player = 2
+#steam_id = 3
+
 # Optionally, specify whether Steam ID translation should occurs (set active = true)
 # You will need to specify some combination of either:
 # 1) a hash algorithm (MD5, SHA1, or SHA256)
@@ -50,17 +79,58 @@ pub struct SteamIdTranslation {
     pub mappings: Option<Vec<SteamIdTranslationMapping>>,
 }
 
+/// A user-defined pattern for lines the parser doesn't otherwise understand
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CustomPattern {
+    pub name: String,
+    pub pattern: String,
+    pub timestamp: Option<usize>,
+    pub fields: Option<HashMap<String, usize>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
     pub log_dir: std::path::PathBuf,
     pub delay: u64,
+    pub timezone: Option<chrono_tz::Tz>,
+    pub include_bots: Option<bool>,
+    pub emit_unparsed: Option<bool>,
+    pub trade_window: Option<i64>,
     pub steam_id_translation: Option<SteamIdTranslation>,
+    pub custom_patterns: Option<Vec<CustomPattern>>,
 }
 
-impl Config {
-    pub fn default() -> Config {
+impl Default for Config {
+    fn default() -> Config {
         toml::from_str(DEFAULT_CONFIG).unwrap()
     }
+}
+
+impl Config {
+    /// Log timestamps are assumed to be UTC unless the config says otherwise
+    pub fn timezone(&self) -> chrono_tz::Tz {
+        self.timezone.unwrap_or(chrono_tz::UTC)
+    }
+
+    /// Bot events are included unless the config explicitly says otherwise
+    pub fn include_bots(&self) -> bool {
+        self.include_bots.unwrap_or(true)
+    }
+
+    /// Unparsed lines are only counted, not emitted, unless the config says otherwise
+    pub fn emit_unparsed(&self) -> bool {
+        self.emit_unparsed.unwrap_or(false)
+    }
+
+    /// Deaths are traded if the killer is killed within 5 seconds, unless the config says otherwise
+    pub fn trade_window(&self) -> i64 {
+        self.trade_window.unwrap_or(state::TRADE_WINDOW_SECONDS)
+    }
+
+    /// The user-defined line patterns, if there are any
+    pub fn custom_patterns(&self) -> &[CustomPattern] {
+        self.custom_patterns.as_deref().unwrap_or_default()
+    }
 
     pub fn read_from_file(file_path: &std::path::Path) -> Option<Config> {
         if file_path.exists() {
@@ -104,7 +174,7 @@ impl Config {
         }
 
         // Create a new config file
-        let mut config_file = match std::fs::File::create(&file_path) {
+        let mut config_file = match std::fs::File::create(file_path) {
             Err(why) => panic!("Failed to create file at {}: {}", file_path.display(), why),
             Ok(file) => file,
         };
@@ -130,11 +200,7 @@ impl Config {
         if let Some(config_file_path) =
             Self::locate_config_file(std::path::Path::new(CONFIG_FILE_NAME))
         {
-            if let Some(config) = Self::read_from_file(&config_file_path) {
-                config
-            } else {
-                Self::default()
-            }
+            Self::read_from_file(&config_file_path).unwrap_or_default()
         } else {
             Self::default()
         }
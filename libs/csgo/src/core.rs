@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
 use super::config;
+use super::coverage;
+use super::dialect;
 use super::geo;
 use super::logs;
 use super::rgx;
@@ -10,22 +12,54 @@ use super::utils;
 pub struct Engine {
     reader: logs::Reader,
     game_state: state::GameState,
+    coverage: coverage::Coverage,
+    clock: utils::Clock,
+    dialect: dialect::Normaliser,
+    custom_patterns: Vec<rgx::CustomPattern>,
+    /// Whether the server is in its warmup, i.e. it's logged `Warmup_Start` but not `Warmup_End` yet
+    warmup: bool,
     config: config::Config,
 }
 
 impl Engine {
     /// Create a new Engine with the specified Config
     ///
-    /// ```rust
-    /// let mut engine = csgo::core::Engine::new(std::path::PathBuf::from("path/to/log/dir"), 2); // Every 2 second, `engine` will look for log files within the directory at `path/to/log/dir`
+    /// ```no_run
+    /// let mut config = csgo::config::Config::default();
+    /// config.log_dir = std::path::PathBuf::from("path/to/log/dir");
+    /// config.delay = 2;
+    ///
+    /// let mut engine = csgo::core::Engine::new(config); // Every 2 seconds, `engine` will look for log files within the directory at `path/to/log/dir`
     ///
     /// engine.run(); // Start the engine
     /// ```
     pub fn new(config: config::Config) -> Engine {
         log::info!("Using config: {:?}", &config);
+
+        // A bad pattern shouldn't stop everything else being parsed
+        let custom_patterns = config
+            .custom_patterns()
+            .iter()
+            .filter_map(|pattern| match rgx::CustomPattern::compile(pattern) {
+                Ok(compiled) => Some(compiled),
+                Err(why) => {
+                    log::error!("Ignoring custom pattern '{}': {}", pattern.name, why);
+                    None
+                }
+            })
+            .collect();
+
+        let mut game_state = state::GameState::new();
+        game_state.set_trade_window(config.trade_window());
+
         Engine {
             reader: logs::Reader::new(&config.log_dir),
-            game_state: state::GameState::new(),
+            game_state,
+            coverage: coverage::Coverage::new(),
+            clock: utils::Clock::new(config.timezone()),
+            dialect: dialect::Normaliser::new(),
+            custom_patterns,
+            warmup: false,
             config,
         }
     }
@@ -42,123 +76,621 @@ impl Engine {
             // Convert the data vec into a string
             let data = std::str::from_utf8(&data).unwrap_or_default();
 
-            // If the data is not empty, split into lines and parse
-            if !data.is_empty() {
-                // Benchmarking, just for interest
-                let now = std::time::Instant::now();
-                let mut line_count = 0;
+            self.parse_data(data);
 
-                for line in data.lines() {
-                    // Parse the line
-                    let json_vec = self.parse_line(line);
+            // Sleep
+            log::trace!("Sleeping for {}s", self.config.delay);
+            std::thread::sleep(std::time::Duration::from_secs(self.config.delay));
+        }
+    }
 
-                    // Dispatch the results
-                    if let Some(json_vec) = json_vec {
-                        Engine::dispatch(json_vec);
+    /// Parse every log file in the log directory once, oldest first, and then report how many of the lines were understood
+    pub fn run_batch(&mut self) {
+        for log_file in self.reader.log_files() {
+            log::info!("Parsing log file '{}'", log_file.display());
 
-                        /*for json in json_vec.iter() {
-                            log::info!("JSON: {}", json);
-                        }*/
-                    }
+            let data = match std::fs::read(&log_file) {
+                Ok(data) => data,
+                Err(why) => {
+                    log::error!("Failed to read {}: {}", log_file.display(), why);
+                    continue;
+                }
+            };
+
+            // Convert the data vec into a string
+            let data = std::str::from_utf8(&data).unwrap_or_default();
+
+            self.parse_data(data);
+        }
+
+        log::info!("Coverage report:\n{}", self.coverage);
+    }
+
+    /// The state of the match being parsed, including each round played so far
+    pub fn game_state(&self) -> &state::GameState {
+        &self.game_state
+    }
+
+    /// How many of the lines seen so far were understood by the parser, and the shapes of the ones that weren't
+    pub fn coverage(&self) -> &coverage::Coverage {
+        &self.coverage
+    }
+
+    /// Split the data into lines, then parse and dispatch each of them
+    fn parse_data(&mut self, data: &str) {
+        // If the data is not empty, split into lines and parse
+        if !data.is_empty() {
+            // Benchmarking, just for interest
+            let now = std::time::Instant::now();
+            let mut line_count = 0;
 
-                    line_count += 1;
+            for line in data.lines() {
+                // Rewrite the line into the CS:GO form if it's from CS2, or hold onto it if it's part of a multi-line block
+                let line = match self.dialect.normalise(line) {
+                    Some(line) => line,
+                    None => continue,
+                };
+
+                // Parse the line
+                let json_vec = self.parse_line(&line);
+
+                // Dispatch the results
+                if let Some(json_vec) = json_vec {
+                    Engine::dispatch(json_vec);
                 }
 
-                log::info!(
-                    "Parsed {} lines in {} ms",
-                    line_count,
-                    now.elapsed().as_millis()
-                );
+                line_count += 1;
             }
 
-            // Sleep
-            log::trace!("Sleeping for {}s", self.config.delay);
-            std::thread::sleep(std::time::Duration::from_secs(self.config.delay as u64));
+            log::info!(
+                "Parsed {} lines in {} ms",
+                line_count,
+                now.elapsed().as_millis()
+            );
+        }
+    }
+
+    /// Should an event involving these players be left out of the stats because one of them is a bot?
+    fn excluded_bots(&self, players: &[&rgx::PlayerTuple]) -> bool {
+        !self.config.include_bots() && players.iter().any(|player| utils::is_bot(player.steam_id))
+    }
+
+    /// Add the name, ID, userid, bot flag and team of a player to the event detail, with each field name beginning with the given prefix (e.g. "player" or "victim").
+    /// The player's identity is updated as well, in case their userid or name has changed
+    fn insert_player(
+        &mut self,
+        event_detail: &mut HashMap<String, String>,
+        prefix: &str,
+        player: &rgx::PlayerTuple,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+    ) {
+        let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+
+        self.game_state.see_player(
+            &player_id,
+            player.steam_id,
+            player.userid,
+            player.name,
+            datetime.timestamp(),
+        );
+
+        event_detail.insert(prefix.to_owned(), player.name.to_owned());
+        event_detail.insert(format!("{}_id", prefix), player_id);
+        event_detail.insert(format!("{}_userid", prefix), player.userid.to_owned());
+        event_detail.insert(
+            format!("{}_bot", prefix),
+            utils::is_bot(player.steam_id).to_string(),
+        );
+        event_detail.insert(
+            format!("{}_team", prefix),
+            player.team.unwrap_or_default().to_owned(),
+        );
+    }
+
+    /// The common fields for a highlight of a player's round, e.g. a multi-kill
+    fn highlight_detail(
+        &self,
+        event_type: &str,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+        player_id: &str,
+    ) -> HashMap<String, String> {
+        let mut event_detail = self.event_detail(event_type, datetime);
+        event_detail.insert(
+            "round_number".to_owned(),
+            self.game_state.round_number().to_string(),
+        );
+        event_detail.insert("player_id".to_owned(), player_id.to_owned());
+        if let Some(identity) = self.game_state.players().get(player_id) {
+            event_detail.insert("player".to_owned(), identity.name().to_owned());
+        }
+
+        event_detail
+    }
+
+    /// A 1vX clutch, with its outcome if the round is over
+    fn clutch_detail(
+        &self,
+        event_type: &str,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+        clutch: &state::Clutch,
+    ) -> HashMap<String, String> {
+        let mut event_detail = self.highlight_detail(event_type, datetime, clutch.player());
+        event_detail.insert("player_team".to_owned(), clutch.side().to_owned());
+        event_detail.insert("opponents".to_owned(), clutch.opponents().to_string());
+        event_detail.insert("clutch".to_owned(), format!("1v{}", clutch.opponents()));
+        if let Some(won) = clutch.won() {
+            event_detail.insert("won".to_owned(), won.to_string());
+        }
+
+        event_detail
+    }
+
+    /// Create the detail fields for a player's match result, i.e. a match_win, match_loss or match_draw event
+    fn result_detail(
+        &self,
+        event_type: &str,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+        player: &state::Player,
+    ) -> HashMap<String, String> {
+        let mut event_detail = self.event_detail(event_type, datetime);
+        event_detail.insert("player".to_owned(), player.name().to_owned());
+        event_detail.insert("player_id".to_owned(), player.id().to_owned());
+        event_detail.insert("player_bot".to_owned(), player.bot().to_string());
+
+        event_detail
+    }
+
+    /// Create the detail fields common to every event
+    fn event_detail(
+        &self,
+        event_type: &str,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+    ) -> HashMap<String, String> {
+        let mut event_detail = HashMap::new();
+        event_detail.insert("event_type".to_owned(), event_type.to_owned());
+        event_detail.insert("timestamp".to_owned(), datetime.to_rfc3339());
+        event_detail.insert(
+            "game_map".to_owned(),
+            self.game_state.map().name().to_owned(),
+        );
+        event_detail.insert(
+            "match_phase".to_owned(),
+            self.game_state.phase().name().to_owned(),
+        );
+
+        if let Some(mode) = self.game_state.mode() {
+            event_detail.insert("game_mode".to_owned(), mode.name().to_owned());
         }
+
+        event_detail
+    }
+
+    /// Move the match on to the given phase, returning the JSON for a phase_change event if it actually changed
+    fn change_phase(
+        &mut self,
+        phase: state::MatchPhase,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+    ) -> Option<String> {
+        let previous_phase = self.game_state.set_phase(phase)?;
+
+        Some(self.phase_changed(previous_phase, datetime))
+    }
+
+    /// Record that the match has moved on from the given phase to its current one, returning the event's JSON
+    fn phase_changed(
+        &mut self,
+        previous_phase: state::MatchPhase,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+    ) -> String {
+        log::info!(
+            "Match phase: {} -> {}",
+            previous_phase.name(),
+            self.game_state.phase().name()
+        );
+
+        let mut event_detail = self.event_detail("phase_change", datetime);
+        event_detail.insert(
+            "previous_phase".to_owned(),
+            previous_phase.name().to_owned(),
+        );
+
+        self.record_event(datetime, event_detail)
+    }
+
+    /// Add the event to the game_state events buffer, returning its JSON representation so it can be dispatched
+    fn record_event(
+        &mut self,
+        datetime: &chrono::DateTime<chrono_tz::Tz>,
+        event_detail: HashMap<String, String>,
+    ) -> String {
+        let event = state::Event::new(datetime.timestamp(), event_detail);
+        let json = serde_json::to_string(&event).unwrap();
+
+        self.game_state.add_event(event);
+
+        json
+    }
+
+    /// Convert a log timestamp into a date/time, going by the latest one seen if it isn't valid
+    fn datetime(&mut self, timestamp: &str) -> chrono::DateTime<chrono_tz::Tz> {
+        self.clock.datetime(timestamp).unwrap_or_else(|| {
+            log::warn!(
+                "Invalid timestamp '{}', using the latest one seen",
+                timestamp
+            );
+            self.clock.latest()
+        })
+    }
+
+    /// Check the line against the user-defined patterns from the config, and if one matches, record an event named after it, returning its JSON
+    fn custom_event(&mut self, line: &str) -> Option<String> {
+        let (name, timestamp, fields) = self.custom_patterns.iter().find_map(|pattern| {
+            let (timestamp, fields) = pattern.captures(line)?;
+            Some((pattern.name().to_owned(), timestamp, fields))
+        })?;
+
+        log::debug!("Custom Pattern '{}': {:?}", name, &fields);
+
+        // Fall back on the usual timestamp at the start of the line if the pattern doesn't capture one, or what it captures isn't a timestamp
+        let datetime = match timestamp.map(|timestamp| (timestamp, self.clock.datetime(timestamp)))
+        {
+            Some((_, Some(datetime))) => datetime,
+            captured => {
+                if let Some((timestamp, _)) = captured {
+                    log::warn!(
+                        "Custom pattern '{}' captured '{}' as its timestamp, which isn't one",
+                        name,
+                        timestamp
+                    );
+                }
+
+                match rgx::timestamp(line) {
+                    Some(captures) => self.datetime(&captures[1]),
+                    None => self.clock.latest(),
+                }
+            }
+        };
+
+        let mut event_detail = self.event_detail(&name, &datetime);
+
+        // The common fields take precedence, so custom events can be handled just like built-in ones
+        for (field, value) in fields {
+            event_detail.entry(field).or_insert(value);
+        }
+
+        Some(self.record_event(&datetime, event_detail))
     }
 
     /// Check which regex pattern matched for the given line, if any, and update the game state accordingly
     fn parse_line(&mut self, line: &str) -> Option<Vec<String>> {
         log::trace!("Parsing line: {}", line);
 
-        if let Some(captures) = rgx::match_start(line) {
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        let mut json_vec = Vec::new();
+
+        if let Some(captures) = rgx::server_cvar(line) {
+            log::debug!("Server Cvar: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let cvar = &captures[2];
+            let value = &captures[3];
+
+            self.game_state.rules_mut().set_cvar(cvar, value);
+
+            match cvar {
+                "mp_teamname_1" if !value.is_empty() => self.game_state.rename_team(1, value),
+                "mp_teamname_2" if !value.is_empty() => self.game_state.rename_team(2, value),
+                "game_type" | "game_mode" => {
+                    let rules = self.game_state.rules();
+                    if let (Some(game_type), Some(game_mode)) =
+                        (rules.cvar("game_type"), rules.cvar("game_mode"))
+                    {
+                        let mode = state::GameMode::from_cvars(game_type, game_mode);
+                        self.game_state.set_mode(mode);
+                    }
+                }
+                _ => {}
+            }
+
+            let mut event_detail = self.event_detail("server_cvar", &datetime);
+            event_detail.insert("cvar".to_owned(), cvar.to_owned());
+            event_detail.insert("value".to_owned(), value.to_owned());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::match_status_team(line) {
+            log::debug!("Match Status Team: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let side = &captures[2];
+            let clan_name = captures[3].trim();
+
+            // Servers without mp_teamname_* set still write these lines, with no name
+            if !clan_name.is_empty() {
+                self.game_state.set_clan_name(side, clan_name);
+
+                let mut event_detail = self.event_detail("team_name", &datetime);
+                event_detail.insert("side".to_owned(), side.to_owned());
+                event_detail.insert("team_name".to_owned(), clan_name.to_owned());
+
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+        } else if let Some(captures) = rgx::match_status_score(line) {
+            log::debug!("Match Status Score: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let ct_score = &captures[2];
+            let terrorist_score = &captures[3];
+
+            // Cross-check the server's score with the rounds we've seen each team win
+            let score_of = |side: &str| {
+                self.game_state
+                    .team_on_side(side)
+                    .map(|team| team.score().to_string())
+                    .unwrap_or_default()
+            };
+            let score_matches =
+                score_of("CT") == ct_score && score_of("TERRORIST") == terrorist_score;
+
+            if !score_matches {
+                log::warn!(
+                    "Score mismatch: server says {}:{}, but we counted {}:{}",
+                    ct_score,
+                    terrorist_score,
+                    score_of("CT"),
+                    score_of("TERRORIST")
+                );
+            }
+
+            let mut event_detail = self.event_detail("match_status", &datetime);
+            event_detail.insert("ct_score".to_owned(), ct_score.to_owned());
+            event_detail.insert("terrorist_score".to_owned(), terrorist_score.to_owned());
+            event_detail.insert("map".to_owned(), captures[4].to_owned());
+            event_detail.insert("rounds_played".to_owned(), captures[5].to_owned());
+            event_detail.insert("score_matches".to_owned(), score_matches.to_string());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::rcon(line) {
+            log::debug!("Rcon: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+
+            let mut event_detail = self.event_detail("rcon", &datetime);
+            event_detail.insert("address".to_owned(), captures[2].to_owned());
+            event_detail.insert("command".to_owned(), captures[3].to_owned());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::loading_map(line) {
+            log::debug!("Loading Map: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+
+            let mut event_detail = self.event_detail("loading_map", &datetime);
+            event_detail.insert("next_map".to_owned(), captures[2].to_owned());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::started_map(line) {
+            log::debug!("Started Map: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+
+            // The server has changed level, so the map we knew about is no longer the one being played
+            if self.game_state.map().name() != &captures[2] {
+                *self.game_state.map_mut() = state::Map::from_name(&captures[2]);
+            }
+
+            let mut event_detail = self.event_detail("started_map", &datetime);
+            if let Some(crc) = captures.get(3) {
+                event_detail.insert("crc".to_owned(), crc.as_str().to_owned());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::match_start(line) {
             log::debug!("Match Started: {:?}", &captures);
 
+            let datetime = self.datetime(&captures[1]);
+
             // In case a new match was started before the previous one ended, clear out the game_state and start afresh
-            self.game_state = state::GameState::new();
-            *self.game_state.map_mut() = state::Map::from_name(&captures[1]);
+            // The server rules are a property of the server rather than the match, and players stay on their teams, so they carry over
+            self.game_state.reset();
+            *self.game_state.map_mut() = state::Map::from_name(&captures[2]);
 
             log::info!("{:?}", &self.game_state);
-        }
 
-        if let Some(captures) = rgx::switched_team(line) {
+            // Match_Start is logged when the warmup begins too, in which case the match isn't live until Warmup_End
+            let phase = if self.warmup {
+                state::MatchPhase::Warmup
+            } else {
+                state::MatchPhase::Live
+            };
+            json_vec.extend(self.change_phase(phase, &datetime));
+
+            let event_detail = self.event_detail("match_start", &datetime);
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::match_phase(line) {
+            log::debug!("Match Phase: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+
+            match &captures[2] {
+                "Game_Commencing" | "Warmup_Start" => {
+                    self.warmup = &captures[2] == "Warmup_Start";
+                    json_vec.extend(self.change_phase(state::MatchPhase::Warmup, &datetime));
+                }
+                "Warmup_End" => {
+                    self.warmup = false;
+                    json_vec.extend(self.change_phase(state::MatchPhase::Live, &datetime));
+                }
+                _ => {
+                    // mp_restartgame, which throws away everything that happened so far
+                    self.game_state.reset();
+
+                    if self.game_state.phase() != state::MatchPhase::Warmup {
+                        json_vec.extend(self.change_phase(state::MatchPhase::Live, &datetime));
+                    }
+
+                    let event_detail = self.event_detail("match_restart", &datetime);
+                    json_vec.push(self.record_event(&datetime, event_detail));
+                }
+            }
+        } else if let Some(captures) = rgx::json_block(line) {
+            log::debug!("JSON Block: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let json: serde_json::Value = serde_json::from_str(&captures[2]).unwrap_or_default();
+
+            let mut event_detail = match json["name"].as_str() {
+                Some("round_stats") => {
+                    let mut event_detail = self.event_detail("round_stats", &datetime);
+
+                    for field in ["round_number", "score_ct", "score_t"].iter() {
+                        if let Some(value) = json[field].as_str() {
+                            event_detail.insert(field.to_string(), value.trim().to_owned());
+                        }
+                    }
+
+                    let players = dialect::round_stats_players(&json);
+                    event_detail.insert(
+                        "players".to_owned(),
+                        serde_json::to_string(&players).unwrap(),
+                    );
+
+                    event_detail
+                }
+                // Some other kind of block that we don't know the layout of
+                name => {
+                    let mut event_detail = self.event_detail("json_block", &datetime);
+                    event_detail.insert("name".to_owned(), name.unwrap_or_default().to_owned());
+                    event_detail.insert("json".to_owned(), captures[2].to_owned());
+
+                    event_detail
+                }
+            };
+            event_detail.insert(
+                "dialect".to_owned(),
+                self.dialect.dialect().name().to_owned(),
+            );
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::switched_team(line) {
             log::info!("Switched Teams: {:?}", &captures);
 
-            let player_name = captures[1].to_owned();
-            let player_id = utils::translate_steam_id(&captures[2], &self.config);
-            let player = state::Player::new(player_name, player_id);
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let player_bot = utils::is_bot(player.steam_id);
 
-            let team = &captures[3];
+            // Leave bots out of the teams altogether if they're not wanted in the stats
+            if !self.excluded_bots(&[&player]) {
+                self.game_state.see_player(
+                    &player_id,
+                    player.steam_id,
+                    player.userid,
+                    player.name,
+                    datetime.timestamp(),
+                );
 
-            // Add the player to the specified team, removing them from the other team if they were already in-game);
-            self.game_state.add_player_to_team(player, team);
-        }
+                let player = state::Player::new(player.name.to_owned(), player_id, player_bot);
+
+                let previous_team = &captures[3];
+                let team = &captures[4];
+
+                // Everyone swapping sides between rounds means it's halftime
+                if previous_team != "Unassigned" && previous_team != team {
+                    let phase = self.game_state.phase();
+
+                    if self.game_state.swap_side() {
+                        json_vec.push(self.phase_changed(phase, &datetime));
+                    }
+                }
+
+                // Add the player to the specified team, removing them from the other team if they were already in-game);
+                self.game_state.add_player_to_team(player, team);
+            }
+        } else if let Some(captures) = rgx::changed_name(line) {
+            log::debug!("Changed Name: {:?}", &captures);
+
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring name change by a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let new_name = &captures[3];
 
-        if let Some(captures) = rgx::attack(line) {
+            let mut event_detail = self.event_detail("name_change", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("new_name".to_owned(), new_name.to_owned());
+
+            self.game_state.see_player(
+                &player_id,
+                player.steam_id,
+                player.userid,
+                new_name,
+                datetime.timestamp(),
+            );
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::attack(line) {
             log::debug!("Attack: {:?}", &captures);
 
+            let player = rgx::player(&captures[2]);
+            let victim = rgx::player(&captures[4]);
+
+            if self.excluded_bots(&[&player, &victim]) {
+                log::trace!("Ignoring attack involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
             // Timestamp
-            let epoch = utils::timestamp_to_epoch(&captures[1]);
+            let datetime = self.datetime(&captures[1]);
 
             // Attacking player
-            let player_name = captures[2].to_owned();
-            let player_id = utils::translate_steam_id(&captures[3], &self.config);
-            let player_team = captures[4].to_owned();
-            let player_position = captures[5].to_owned();
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let player_position = captures[3].to_owned();
             let player_lat_lon = geo::game_pos_to_decimal_degrees(&player_position);
 
             // Victim of attack
-            let victim_name = captures[6].to_owned();
-            let victim_id = utils::translate_steam_id(&captures[7], &self.config);
-            let victim_team = captures[8].to_owned();
-            let victim_position = captures[9].to_owned();
+            let victim_id = utils::player_id(victim.name, victim.steam_id, &self.config);
+            let victim_position = captures[5].to_owned();
             let victim_lat_lon = geo::game_pos_to_decimal_degrees(&victim_position);
 
             // Distance between players
             let distance = geo::metres_between_points(&player_position, &victim_position);
 
             // Weapon used
-            let weapon = captures[10].to_owned();
+            let weapon = captures[6].to_owned();
 
             // Damage dealt to victim
-            let damage_health = captures[11].to_owned();
-            let damage_armor = captures[12].to_owned();
+            let damage_health = captures[7].to_owned();
+            let damage_armor = captures[8].to_owned();
 
             // Victim health remaining
-            let health_remaining = captures[13].to_owned();
-            let armor_remaining = captures[14].to_owned();
+            let health_remaining = captures[9].to_owned();
+            let armor_remaining = captures[10].to_owned();
 
             // Bodypart hit
-            let hitgroup = captures[15].to_owned();
+            let hitgroup = captures[11].to_owned();
 
-            let mut event_detail = HashMap::new();
-            event_detail.insert("event_type".to_owned(), "attack".to_owned());
-            event_detail.insert("timestamp".to_owned(), epoch.to_string());
+            let mut event_detail = self.event_detail("attack", &datetime);
 
             // Was it self-inflicted?
             if player_id == victim_id {
                 event_detail.insert("self_inflicted".to_owned(), "true".to_owned());
             }
 
-            event_detail.insert("player".to_owned(), player_name);
-            event_detail.insert("player_id".to_owned(), player_id);
-            event_detail.insert("player_team".to_owned(), player_team);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
             event_detail.insert("player_position".to_owned(), player_position);
             event_detail.insert("player_lat_lon".to_owned(), player_lat_lon);
 
-            event_detail.insert("victim".to_owned(), victim_name);
-            event_detail.insert("victim_id".to_owned(), victim_id);
-            event_detail.insert("victim_team".to_owned(), victim_team);
+            self.insert_player(&mut event_detail, "victim", &victim, &datetime);
             event_detail.insert("victim_position".to_owned(), victim_position);
             event_detail.insert("victim_lat_lon".to_owned(), victim_lat_lon);
 
@@ -171,101 +703,925 @@ impl Engine {
             event_detail.insert("armor_remaining".to_owned(), armor_remaining);
             event_detail.insert("hitgroup".to_owned(), hitgroup);
 
-            event_detail.insert(
-                "game_map".to_owned(),
-                self.game_state.map().name().to_owned(),
+            let health_lost = self.game_state.player_hurt(
+                &victim_id,
+                captures[9].parse().unwrap_or_default(),
+                captures[10].parse().unwrap_or_default(),
             );
 
-            let event = state::Event::new(epoch, event_detail);
+            // Only damage to enemies counts towards the scoreboard, and no more than the health the victim had left
+            let damage = captures[7]
+                .parse::<u32>()
+                .unwrap_or_default()
+                .min(health_lost);
+            let friendly = player.team == victim.team;
 
-            // Add the event to the game_state events buffer
-            self.game_state.events_mut().push(event);
-        }
+            if player_id != victim_id {
+                self.game_state
+                    .record_damage(&player_id, &victim_id, damage, friendly);
+            }
+
+            if player_id != victim_id && !friendly {
+                self.game_state.record_hit(
+                    state::Player::new(
+                        player.name.to_owned(),
+                        player_id.clone(),
+                        utils::is_bot(player.steam_id),
+                    ),
+                    &victim_id,
+                    &captures[6],
+                    &captures[11],
+                    damage,
+                    datetime.timestamp(),
+                );
+            }
 
-        if let Some(captures) = rgx::kill(line) {
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::kill(line) {
             log::debug!("Kill: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::assist(line) {
+            let player = rgx::player(&captures[2]);
+            let victim = rgx::player(&captures[4]);
+
+            if self.excluded_bots(&[&player, &victim]) {
+                log::trace!("Ignoring kill involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let player_position = captures[3].to_owned();
+            let player_lat_lon = geo::game_pos_to_decimal_degrees(&player_position);
+
+            let victim_id = utils::player_id(victim.name, victim.steam_id, &self.config);
+            let victim_position = captures[5].to_owned();
+            let victim_lat_lon = geo::game_pos_to_decimal_degrees(&victim_position);
+
+            let distance = geo::metres_between_points(&player_position, &victim_position);
+            let weapon = captures[6].to_owned();
+
+            // e.g. "headshot", or "headshot penetrated"
+            let flags: Vec<&str> = captures
+                .get(7)
+                .map(|flags| flags.as_str().split_whitespace().collect())
+                .unwrap_or_default();
+
+            let team_kill = player.team == victim.team && player_id != victim_id;
+
+            let (trade, opening) = self.game_state.record_kill(
+                state::Player::new(
+                    player.name.to_owned(),
+                    player_id.clone(),
+                    utils::is_bot(player.steam_id),
+                ),
+                state::Player::new(
+                    victim.name.to_owned(),
+                    victim_id.clone(),
+                    utils::is_bot(victim.steam_id),
+                ),
+                state::Kill::new(
+                    datetime.timestamp(),
+                    player_id.clone(),
+                    player_position.clone(),
+                    victim_id.clone(),
+                    victim_position.clone(),
+                    weapon.clone(),
+                    flags.contains(&"headshot"),
+                    team_kill,
+                ),
+            );
+
+            let clutch = self
+                .game_state
+                .player_died(&victim_id, datetime.timestamp());
+
+            let mut event_detail = self.event_detail("kill", &datetime);
+
+            if player_id == victim_id {
+                event_detail.insert("self_inflicted".to_owned(), "true".to_owned());
+            }
+
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("player_position".to_owned(), player_position);
+            event_detail.insert("player_lat_lon".to_owned(), player_lat_lon);
+
+            self.insert_player(&mut event_detail, "victim", &victim, &datetime);
+            event_detail.insert("victim_position".to_owned(), victim_position);
+            event_detail.insert("victim_lat_lon".to_owned(), victim_lat_lon);
+
+            event_detail.insert("distance".to_owned(), distance);
+            event_detail.insert("weapon".to_owned(), weapon);
+            event_detail.insert("team_kill".to_owned(), team_kill.to_string());
+            event_detail.insert(
+                "headshot".to_owned(),
+                flags.contains(&"headshot").to_string(),
+            );
+
+            for flag in flags.iter().filter(|flag| **flag != "headshot") {
+                event_detail.insert(flag.to_string(), "true".to_owned());
+            }
+
+            let player_team = player.team.unwrap_or_default().to_owned();
+            let victim_team = victim.team.unwrap_or_default().to_owned();
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+
+            if opening {
+                let mut event_detail = self.highlight_detail("opening_kill", &datetime, &player_id);
+                event_detail.insert("player_team".to_owned(), player_team);
+                event_detail.insert("victim".to_owned(), victim.name.to_owned());
+                event_detail.insert("victim_id".to_owned(), victim_id.clone());
+                event_detail.insert("victim_team".to_owned(), victim_team);
+
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+
+            if let Some(trade) = trade {
+                let mut event_detail =
+                    self.highlight_detail("trade_kill", &datetime, trade.player());
+                event_detail.insert("victim_id".to_owned(), trade.victim().to_owned());
+                event_detail.insert("victim".to_owned(), victim.name.to_owned());
+                event_detail.insert("traded_id".to_owned(), trade.traded().to_owned());
+                if let Some(identity) = self.game_state.players().get(trade.traded()) {
+                    event_detail.insert("traded".to_owned(), identity.name().to_owned());
+                }
+                event_detail.insert("trade_time".to_owned(), trade.seconds().to_string());
+                event_detail.insert("distance".to_owned(), trade.distance().to_owned());
+
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+
+            if let Some(clutch) = clutch {
+                let event_detail = self.clutch_detail("clutch_start", &datetime, &clutch);
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+        } else if let Some(captures) = rgx::assist(line) {
             log::debug!("Assist: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::suicide(line) {
+            let player = rgx::player(&captures[2]);
+            let victim = rgx::player(&captures[3]);
+
+            if self.excluded_bots(&[&player, &victim]) {
+                log::trace!("Ignoring assist involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+
+            self.game_state.record_assist(state::Player::new(
+                player.name.to_owned(),
+                player_id,
+                utils::is_bot(player.steam_id),
+            ));
+
+            let mut event_detail = self.event_detail("assist", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            self.insert_player(&mut event_detail, "victim", &victim, &datetime);
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::suicide(line) {
             log::debug!("Suicide: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::blinded(line) {
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring suicide by a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let player_position = captures[3].to_owned();
+            let player_lat_lon = geo::game_pos_to_decimal_degrees(&player_position);
+            let weapon = captures[4].to_owned();
+
+            let clutch = self
+                .game_state
+                .player_died(&player_id, datetime.timestamp());
+
+            let cause = self.game_state.add_suicide(
+                datetime.timestamp(),
+                state::Player::new(
+                    player.name.to_owned(),
+                    player_id,
+                    utils::is_bot(player.steam_id),
+                ),
+                player_position.clone(),
+                weapon.clone(),
+            );
+
+            let mut event_detail = self.event_detail("suicide", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("player_position".to_owned(), player_position);
+            event_detail.insert("player_lat_lon".to_owned(), player_lat_lon);
+            event_detail.insert("weapon".to_owned(), weapon);
+            event_detail.insert("cause".to_owned(), cause.name().to_owned());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+
+            if let Some(clutch) = clutch {
+                let event_detail = self.clutch_detail("clutch_start", &datetime, &clutch);
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+        } else if let Some(captures) = rgx::blinded(line) {
             log::debug!("Blinded: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::bomb(line) {
+            let victim = rgx::player(&captures[2]);
+            let player = rgx::player(&captures[4]);
+
+            if self.excluded_bots(&[&player, &victim]) {
+                log::trace!("Ignoring blinding involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let victim_id = utils::player_id(victim.name, victim.steam_id, &self.config);
+            let duration = captures[3].to_owned();
+            let entindex = captures[5].to_owned();
+
+            // Flashing yourself counts as a team flash
+            let team_flash = player.team == victim.team;
+
+            self.game_state.add_blind(
+                datetime.timestamp(),
+                state::Player::new(
+                    player.name.to_owned(),
+                    player_id.clone(),
+                    utils::is_bot(player.steam_id),
+                ),
+                victim_id.clone(),
+                duration.parse::<f64>().unwrap_or_default(),
+                team_flash,
+                entindex.parse::<u32>().unwrap_or_default(),
+            );
+
+            let mut event_detail = self.event_detail("blinded", &datetime);
+
+            if player_id == victim_id {
+                event_detail.insert("self_inflicted".to_owned(), "true".to_owned());
+            }
+
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            self.insert_player(&mut event_detail, "victim", &victim, &datetime);
+            event_detail.insert("duration".to_owned(), duration);
+            event_detail.insert("team_flash".to_owned(), team_flash.to_string());
+            event_detail.insert("entindex".to_owned(), entindex);
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::round_start(line) {
+            log::debug!("Round Start: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let phase = self.game_state.phase();
+            let round_number = self.game_state.start_round(datetime.timestamp());
+
+            if self.game_state.phase() != phase {
+                json_vec.push(self.phase_changed(phase, &datetime));
+            }
+
+            let mut event_detail = self.event_detail("round_start", &datetime);
+            event_detail.insert("round_number".to_owned(), round_number.to_string());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::round_end(line) {
+            log::debug!("Round End: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+
+            // If the bomb was still ticking, the round was decided some other way
+            if let Some(plant) = self.game_state.active_bomb_plant_mut() {
+                plant.resolve(state::BombOutcome::RoundEnded, datetime.timestamp());
+            }
+
+            let mut event_detail = self.event_detail("round_end", &datetime);
+            event_detail.insert(
+                "round_number".to_owned(),
+                self.game_state.round_number().to_string(),
+            );
+
+            if let Some(round) = self.game_state.current_round() {
+                let duration = datetime.timestamp() - round.start_epoch();
+                event_detail.insert("round_duration".to_owned(), duration.to_string());
+
+                if let Some(winner) = round.winner() {
+                    event_detail.insert("winner".to_owned(), winner.to_owned());
+                }
+                if let Some(reason) = round.reason() {
+                    event_detail.insert("reason".to_owned(), reason.to_owned());
+                }
+            }
+
+            // Summarise the hostages on maps that have them
+            if let Some(status) = self.game_state.hostage_status() {
+                event_detail.insert(
+                    "hostages_rescued".to_owned(),
+                    status.rescued_by().len().to_string(),
+                );
+                event_detail.insert(
+                    "hostages_rescued_by".to_owned(),
+                    serde_json::to_string(status.rescued_by()).unwrap(),
+                );
+                event_detail.insert(
+                    "hostages_killed".to_owned(),
+                    status.killed_by().len().to_string(),
+                );
+            } else if self.game_state.map().is_hostage_map() {
+                event_detail.insert("hostages_rescued".to_owned(), "0".to_owned());
+                event_detail.insert("hostages_rescued_by".to_owned(), "[]".to_owned());
+                event_detail.insert("hostages_killed".to_owned(), "0".to_owned());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+
+            // Highlights of the round
+            let (multi_kills, clutch) = match self.game_state.current_round() {
+                Some(round) => (round.multi_kills(), round.clutch().cloned()),
+                None => (Vec::new(), None),
+            };
+
+            for (player_id, kills) in multi_kills {
+                let mut event_detail = self.highlight_detail("multi_kill", &datetime, &player_id);
+                event_detail.insert("kills".to_owned(), kills.to_string());
+                event_detail.insert(
+                    "multi_kill".to_owned(),
+                    state::multi_kill_name(kills).to_owned(),
+                );
+
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+
+            if let Some(clutch) = clutch {
+                let event_detail = self.clutch_detail("clutch", &datetime, &clutch);
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+
+            let phase = self.game_state.phase();
+            if self.game_state.end_round(datetime.timestamp()) {
+                json_vec.push(self.phase_changed(phase, &datetime));
+            }
+        } else if let Some(captures) = rgx::bomb(line) {
             log::debug!("Bomb: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::hostage(line) {
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            // Checked before the plant is recorded, so a bot's plant can't count towards anyone's stats
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring bomb event involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let site = captures.get(4).map(|site| site.as_str().to_owned());
+
+            let event_type = match &captures[3] {
+                "Got_The_Bomb" | "Spawned_With_The_Bomb" => "bomb_pickup",
+                "Dropped_The_Bomb" => "bomb_drop",
+                "Bomb_Begin_Plant" => "bomb_begin_plant",
+                "Planted_The_Bomb" => "bomb_plant",
+                "Begin_Bomb_Defuse_With_Kit" | "Begin_Bomb_Defuse_Without_Kit" => {
+                    "bomb_begin_defuse"
+                }
+                _ => "bomb_defuse",
+            };
+
+            let mut event_detail = self.event_detail(event_type, &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+
+            match event_type {
+                "bomb_plant" => {
+                    self.game_state
+                        .plant_bomb(site.clone(), player_id, datetime.timestamp());
+                }
+                "bomb_begin_defuse" => {
+                    let kit = &captures[3] == "Begin_Bomb_Defuse_With_Kit";
+                    event_detail.insert("kit".to_owned(), kit.to_string());
+                }
+                "bomb_defuse" => {
+                    if let Some(plant) = self.game_state.active_bomb_plant_mut() {
+                        plant.set_defuser(player_id.clone());
+                        let seconds =
+                            plant.resolve(state::BombOutcome::Defused, datetime.timestamp());
+                        let plant_site = plant.site().map(|site| site.to_owned());
+
+                        event_detail.insert("plant_to_outcome".to_owned(), seconds.to_string());
+                        if let Some(plant_site) = plant_site {
+                            event_detail.insert("bombsite".to_owned(), plant_site);
+                        }
+                    }
+                }
+                _ => {}
+            }
+
+            // Only some lines say which bombsite it was
+            if let Some(site) = site {
+                event_detail.insert("bombsite".to_owned(), site);
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::round_won(line) {
+            log::debug!("Round Won: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let side = &captures[2];
+            let reason = &captures[3];
+            let ct_score = &captures[4];
+            let terrorist_score = &captures[5];
+
+            // The logs don't have a separate line for the bomb exploding
+            if reason == "Target_Bombed" {
+                let mut event_detail = self.event_detail("bomb_explode", &datetime);
+                event_detail.insert("ct_score".to_owned(), ct_score.to_owned());
+                event_detail.insert("terrorist_score".to_owned(), terrorist_score.to_owned());
+
+                if let Some(plant) = self.game_state.active_bomb_plant_mut() {
+                    let seconds = plant.resolve(state::BombOutcome::Exploded, datetime.timestamp());
+                    let plant_site = plant.site().map(|site| site.to_owned());
+
+                    event_detail.insert("plant_to_outcome".to_owned(), seconds.to_string());
+                    if let Some(plant_site) = plant_site {
+                        event_detail.insert("bombsite".to_owned(), plant_site);
+                    }
+                }
+
+                json_vec.push(self.record_event(&datetime, event_detail));
+            }
+
+            let mut event_detail = self.event_detail("round_win", &datetime);
+            event_detail.insert(
+                "round_number".to_owned(),
+                self.game_state.round_number().to_string(),
+            );
+            event_detail.insert("half".to_owned(), self.game_state.half().to_string());
+            event_detail.insert("winner".to_owned(), side.to_owned());
+            event_detail.insert("reason".to_owned(), reason.to_owned());
+            event_detail.insert("ct_score".to_owned(), ct_score.to_owned());
+            event_detail.insert("terrorist_score".to_owned(), terrorist_score.to_owned());
+
+            let winner_team = self
+                .game_state
+                .win_round(side, reason)
+                .map(|team| team.name().to_owned());
+
+            if let Some(round) = self.game_state.current_round_mut() {
+                round.set_result(
+                    side,
+                    winner_team,
+                    reason,
+                    ct_score.parse().unwrap_or_default(),
+                    terrorist_score.parse().unwrap_or_default(),
+                );
+            }
+
+            if let Some(team) = self.game_state.team_on_side(side) {
+                event_detail.insert("winner_team".to_owned(), team.name().to_owned());
+                event_detail.insert("winner_team_score".to_owned(), team.score().to_string());
+                event_detail.insert(
+                    "winner_team_half_scores".to_owned(),
+                    serde_json::to_string(team.half_scores()).unwrap(),
+                );
+            }
+
+            let loser = if side == "CT" { "TERRORIST" } else { "CT" };
+            if let Some(team) = self.game_state.team_on_side(loser) {
+                event_detail.insert("loser_team".to_owned(), team.name().to_owned());
+                event_detail.insert("loser_team_score".to_owned(), team.score().to_string());
+                event_detail.insert(
+                    "loser_team_half_scores".to_owned(),
+                    serde_json::to_string(team.half_scores()).unwrap(),
+                );
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::hostage_carry(line) {
+            log::debug!("Hostage Carry: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring hostage event involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let player_position = captures[3].to_owned();
+            let player_lat_lon = geo::game_pos_to_decimal_degrees(&player_position);
+
+            let event_type = if &captures[4] == "picked up" {
+                self.game_state.hostage_status_mut().pick_up(player_id);
+                "hostage_pickup"
+            } else {
+                self.game_state.hostage_status_mut().drop_off(&player_id);
+                "hostage_drop"
+            };
+
+            let mut event_detail = self.event_detail(event_type, &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("player_position".to_owned(), player_position);
+            event_detail.insert("player_lat_lon".to_owned(), player_lat_lon);
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::hostage(line) {
             log::debug!("Hostage: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::chicken(line) {
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring hostage event involving a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+
+            let event_type = match &captures[3] {
+                "Touched_A_Hostage" => "hostage_touch",
+                "Rescued_A_Hostage" => {
+                    self.game_state.hostage_status_mut().rescue(player_id);
+                    "hostage_rescue"
+                }
+                _ => {
+                    self.game_state.hostage_status_mut().kill(player_id);
+                    "hostage_kill"
+                }
+            };
+
+            let mut event_detail = self.event_detail(event_type, &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::purchased(line) {
+            log::debug!("Purchase: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring purchase by a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let item = &captures[3];
+
+            let price = self.game_state.purchase(&player_id, item);
+
+            let mut event_detail = self.event_detail("purchase", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("item".to_owned(), item.to_owned());
+            if let Some(price) = price {
+                event_detail.insert("price".to_owned(), price.to_string());
+            }
+            if let Some(state) = self.game_state.live_player(&player_id) {
+                event_detail.insert("player_money".to_owned(), state.money().to_string());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::left_buyzone(line) {
+            log::debug!("Left Buyzone: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring a bot leaving the buyzone");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let items: Vec<&str> = captures[3].split_whitespace().collect();
+
+            self.game_state.left_buyzone(&player_id, &items);
+
+            let mut event_detail = self.event_detail("left_buyzone", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            if let Some(state) = self.game_state.live_player(&player_id) {
+                event_detail.insert(
+                    "weapons".to_owned(),
+                    serde_json::to_string(state.weapons()).unwrap(),
+                );
+                event_detail.insert("armor".to_owned(), state.armor().to_string());
+                event_detail.insert("helmet".to_owned(), state.helmet().to_string());
+                event_detail.insert("player_money".to_owned(), state.money().to_string());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::money_change(line) {
+            log::debug!("Money Change: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring money change for a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let money = &captures[5];
+
+            // The server knows better than our estimate
+            self.game_state
+                .set_money(&player_id, money.parse().unwrap_or_default());
+
+            let mut event_detail = self.event_detail("money_change", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("money_before".to_owned(), captures[3].to_owned());
+            event_detail.insert("money_change".to_owned(), captures[4].to_owned());
+            event_detail.insert("player_money".to_owned(), money.to_owned());
+            if let Some(item) = captures.get(6) {
+                event_detail.insert("item".to_owned(), item.as_str().to_owned());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::picked_up(line) {
+            log::debug!("Item Pickup: {:?}", &captures);
+
+            let datetime = self.datetime(&captures[1]);
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring item pickup by a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let item = &captures[3];
+
+            self.game_state.pick_up(&player_id, item);
+
+            let mut event_detail = self.event_detail("item_pickup", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("item".to_owned(), item.to_owned());
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::threw(line) {
+            log::debug!("Threw: {:?}", &captures);
+
+            let player = rgx::player(&captures[2]);
+
+            if self.excluded_bots(&[&player]) {
+                log::trace!("Ignoring grenade thrown by a bot");
+                self.coverage.record_parsed();
+                return None;
+            }
+
+            let datetime = self.datetime(&captures[1]);
+            let player_id = utils::player_id(player.name, player.steam_id, &self.config);
+            let grenade = captures[3].to_owned();
+            let player_position = captures[4].to_owned();
+            let player_lat_lon = geo::game_pos_to_decimal_degrees(&player_position);
+
+            self.game_state.throw_grenade(
+                state::Player::new(
+                    player.name.to_owned(),
+                    player_id,
+                    utils::is_bot(player.steam_id),
+                ),
+                &grenade,
+            );
+
+            let mut event_detail = self.event_detail("grenade_throw", &datetime);
+            self.insert_player(&mut event_detail, "player", &player, &datetime);
+            event_detail.insert("player_position".to_owned(), player_position);
+            event_detail.insert("player_lat_lon".to_owned(), player_lat_lon);
+            event_detail.insert("grenade".to_owned(), grenade);
+            if let Some(entindex) = captures.get(5) {
+                event_detail.insert("entindex".to_owned(), entindex.as_str().to_owned());
+            }
+
+            json_vec.push(self.record_event(&datetime, event_detail));
+        } else if let Some(captures) = rgx::chicken(line) {
             log::debug!("Chicken: {:?}", &captures);
-        }
 
-        if let Some(captures) = rgx::game_over(line) {
+            // Chickens aren't part of the stats
+            self.coverage.record_ignored();
+            return None;
+        } else if let Some(captures) = rgx::game_over(line) {
             log::debug!("Match Ended: {:?}", &captures);
 
-            let epoch = utils::timestamp_to_epoch(&captures[1]);
-            let game_mode = &captures[2];
-            let ct_score = &captures[3];
-            let terrorist_score = &captures[4];
-            let match_duration = &captures[5];
+            let datetime = self.datetime(&captures[1]);
+            let game_mode = state::GameMode::from_name(&captures[2]);
+            let map_group = state::MapGroup::from_name(&captures[3]);
+            let map_name = &captures[4];
+            let ct_score = &captures[5];
+            let terrorist_score = &captures[6];
+            let match_duration = &captures[7];
 
-            *self.game_state.mode_mut() = game_mode.to_string();
+            // The Game Over line has the final say on the map, in case the Match_Start line was missed
+            if self.game_state.map().name() != map_name {
+                log::warn!(
+                    "Game Over map '{}' doesn't match the Match_Start map '{}'",
+                    map_name,
+                    self.game_state.map().name()
+                );
+                *self.game_state.map_mut() = state::Map::from_name(map_name);
+            }
 
-            let mut event_detail = HashMap::new();
-            event_detail.insert("event_type".to_owned(), "game_over".to_owned());
-            event_detail.insert("timestamp".to_owned(), epoch.to_string());
+            self.game_state.set_mode(game_mode.clone());
+            self.game_state.set_map_group(map_group.clone());
+
+            json_vec.extend(self.change_phase(state::MatchPhase::Ended, &datetime));
+
+            let mut event_detail = self.event_detail("game_over", &datetime);
+
+            // The team names are the clan names, if the server gave them
+            for (side, field) in [("CT", "ct_team"), ("TERRORIST", "terrorist_team")].iter() {
+                if let Some(team) = self.game_state.team_on_side(side) {
+                    event_detail.insert(field.to_string(), team.name().to_owned());
+                }
+            }
+            event_detail.insert("map_group".to_owned(), map_group.name().to_owned());
+            event_detail.insert("game_duration".to_owned(), match_duration.to_owned());
             event_detail.insert(
-                "game_map".to_owned(),
-                self.game_state.map().name().to_owned(),
+                "scoreboard".to_owned(),
+                serde_json::to_string(&self.game_state.scoreboard()).unwrap(),
             );
-            event_detail.insert("game_duration".to_owned(), match_duration.to_owned());
 
-            let event = state::Event::new(epoch, event_detail);
+            // Who damaged whom over the whole match, and in each round, keyed by round number
+            let round_damage: std::collections::BTreeMap<u32, &state::DamageMatrix> = self
+                .game_state
+                .rounds()
+                .iter()
+                .map(|round| (round.number(), round.damage()))
+                .collect();
+            event_detail.insert(
+                "damage".to_owned(),
+                serde_json::to_string(self.game_state.damage()).unwrap(),
+            );
+            event_detail.insert(
+                "round_damage".to_owned(),
+                serde_json::to_string(&round_damage).unwrap(),
+            );
+            event_detail.insert(
+                "dialect".to_owned(),
+                self.dialect.dialect().name().to_owned(),
+            );
 
             // Now add events for winning/losing for each player
-            // Ignore Armsrace ("gungameprogressive") games as these don't have a winning team
-            // Also, Deathmatch mode doesn't track the winning team either, for some reason!
-            if game_mode != "gungameprogressive" && game_mode != "deathmatch" {
-                // Who won? Or was it a draw?
-                let ct_score = ct_score.parse::<u32>().unwrap();
-                let terrorist_score = terrorist_score.parse::<u32>().unwrap();
-
-                match ct_score
-                    .partial_cmp(&terrorist_score)
-                    .expect("Impossible comparison!")
-                {
+            // Arms race and deathmatch games don't have a winning team, so the players are ranked instead.
+            // Whoever is top of the ranking wins, unless someone else is level with them on both kills and deaths
+            if !game_mode.has_winning_team() {
+                let ranking: Vec<_> = self
+                    .game_state
+                    .ranking()
+                    .iter()
+                    .map(|score| (score.player().clone(), score.kills(), score.ranking_key()))
+                    .collect();
+
+                let top_key = ranking.first().map(|(_, _, key)| *key);
+                let top_players = ranking
+                    .iter()
+                    .filter(|(_, _, key)| Some(*key) == top_key)
+                    .count();
+
+                for (index, (player, kills, key)) in ranking.iter().enumerate() {
+                    let event_type = if Some(*key) != top_key {
+                        "match_loss"
+                    } else if top_players > 1 {
+                        "match_draw"
+                    } else {
+                        log::info!("{} wins!", player.name());
+                        "match_win"
+                    };
+
+                    let mut event_detail = self.result_detail(event_type, &datetime, player);
+                    event_detail.insert("rank".to_owned(), (index + 1).to_string());
+                    event_detail.insert("kills".to_owned(), kills.to_string());
+
+                    json_vec.push(self.record_event(&datetime, event_detail));
+                }
+
+                let players = ranking
+                    .iter()
+                    .map(|(player, _, _)| player.id().to_owned())
+                    .collect();
+                self.game_state
+                    .set_result(state::MatchResult::Ranking { players });
+            } else if let (Ok(ct_score), Ok(terrorist_score)) =
+                (ct_score.parse::<u32>(), terrorist_score.parse::<u32>())
+            {
+                // Competitive, casual, wingman, etc. Who won? Or was it a draw?
+                let winning_side = match ct_score.cmp(&terrorist_score) {
                     std::cmp::Ordering::Less => {
                         log::info!("Terrorists win!");
+                        Some("TERRORIST")
                     }
                     std::cmp::Ordering::Equal => {
                         log::info!("It's a draw!");
+                        None
                     }
                     std::cmp::Ordering::Greater => {
                         log::info!("Counter-Terrorists win!");
+                        Some("CT")
+                    }
+                };
+
+                for side in ["CT", "TERRORIST"].iter() {
+                    let event_type = match winning_side {
+                        None => "match_draw",
+                        Some(winning_side) if winning_side == *side => "match_win",
+                        Some(_) => "match_loss",
+                    };
+
+                    let team_name = self
+                        .game_state
+                        .team_on_side(side)
+                        .map(|team| team.name().to_owned())
+                        .unwrap_or_default();
+
+                    let players = if *side == "CT" {
+                        self.game_state.cts().players().clone()
+                    } else {
+                        self.game_state.ts().players().clone()
+                    };
+
+                    for player in players.iter() {
+                        let mut event_detail = self.result_detail(event_type, &datetime, player);
+                        event_detail.insert("player_team".to_owned(), side.to_string());
+                        event_detail.insert("team_name".to_owned(), team_name.clone());
+
+                        json_vec.push(self.record_event(&datetime, event_detail));
                     }
                 }
+
+                let winner = winning_side
+                    .and_then(|side| self.game_state.team_on_side(side))
+                    .map(|team| team.name().to_owned());
+
+                if let Some(winner) = &winner {
+                    event_detail.insert("winner".to_owned(), winner.clone());
+                }
+                self.game_state
+                    .set_result(state::MatchResult::Team { winner });
+            } else {
+                log::warn!(
+                    "Can't read the final score {}:{}, so there's no result",
+                    ct_score,
+                    terrorist_score
+                );
             }
 
-            self.game_state.events_mut().push(event);
+            json_vec.push(self.record_event(&datetime, event_detail));
+
+            log::info!("Match contained {} events", self.game_state.events().len());
 
             //log::info!("{:?}", &self.game_state);
 
             let json = serde_json::to_value(&self.game_state).unwrap();
 
             log::info!("{}", serde_json::to_string_pretty(&json).unwrap());
+        } else if let Some(json) = self.custom_event(line) {
+            json_vec.push(json);
+        } else {
+            // None of the patterns matched, so keep track of it in case it's a kind of line we should be handling
+            let shape = self.coverage.record_unparsed(line);
+            log::debug!("Unparsed: {}", line);
+
+            if self.config.emit_unparsed() {
+                let datetime = match rgx::timestamp(line) {
+                    Some(captures) => self.datetime(&captures[1]),
+                    None => self.clock.latest(),
+                };
+
+                let mut event_detail = self.event_detail("unparsed", &datetime);
+                event_detail.insert("line".to_owned(), line.to_owned());
+                event_detail.insert("shape".to_owned(), shape);
+
+                // Unparsed lines are dispatched, but they're not part of the game state
+                let event = state::Event::new(datetime.timestamp(), event_detail);
+                json_vec.push(serde_json::to_string(&event).unwrap());
+            }
+
+            return Self::to_dispatch(json_vec);
         }
 
-        None
+        self.coverage.record_parsed();
+
+        Self::to_dispatch(json_vec)
+    }
+
+    fn to_dispatch(json_vec: Vec<String>) -> Option<Vec<String>> {
+        if json_vec.is_empty() {
+            None
+        } else {
+            Some(json_vec)
+        }
     }
 
     fn dispatch(events: Vec<String>) {
@@ -274,3 +1630,366 @@ impl Engine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_lines(engine: &mut Engine, lines: &str) {
+        for line in lines.lines() {
+            engine.parse_line(line.trim());
+        }
+    }
+
+    #[test]
+    fn test_match_start_during_warmup() {
+        let mut engine = Engine::new(config::Config::default());
+
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:48:00: World triggered "Warmup_Start"
+            L 05/04/2020 - 21:48:01: World triggered "Match_Start" on "de_sugarcane""#,
+        );
+        assert_eq!(engine.game_state().phase(), state::MatchPhase::Warmup);
+
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:49:00: World triggered "Warmup_End"
+            L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane""#,
+        );
+        assert_eq!(engine.game_state().phase(), state::MatchPhase::Live);
+
+        // Without a warmup, the match is live as soon as it starts
+        let mut engine = Engine::new(config::Config::default());
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:48:00: World triggered "Game_Commencing"
+            L 05/04/2020 - 21:48:01: World triggered "Match_Start" on "de_sugarcane""#,
+        );
+        assert_eq!(engine.game_state().phase(), state::MatchPhase::Live);
+    }
+
+    #[test]
+    fn test_team_names_from_cvars() {
+        let mut engine = Engine::new(config::Config::default());
+
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:48:00: server_cvar: "mp_teamname_1" "Apples"
+            L 05/04/2020 - 21:48:00: server_cvar: "mp_teamname_2" "Oranges"
+            L 05/04/2020 - 21:48:01: World triggered "Match_Start" on "de_sugarcane"
+            L 05/04/2020 - 21:48:02: server_cvar: "mp_teamname_2" """#,
+        );
+
+        // Team names carry over to the new match, and clearing the cvar doesn't unname the team
+        assert_eq!(
+            engine.game_state().team_on_side("CT").unwrap().name(),
+            "Apples"
+        );
+        assert_eq!(
+            engine
+                .game_state()
+                .team_on_side("TERRORIST")
+                .unwrap()
+                .name(),
+            "Oranges"
+        );
+    }
+
+    #[test]
+    fn test_free_for_all_result() {
+        let mut engine = Engine::new(config::Config::default());
+
+        // Bob and Dean both have one kill, but Dean died as well
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane"
+            L 05/04/2020 - 21:49:18: "Bob<6><STEAM_1:0:00000002>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:49:18: "Dean<9><STEAM_1:1:00000004>" switched from team <Unassigned> to <TERRORIST>
+            L 05/04/2020 - 21:49:18: "James<3><STEAM_1:0:00000005>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:50:03: "Dean<9><STEAM_1:1:00000004><TERRORIST>" [0 0 0] killed "James<3><STEAM_1:0:00000005><CT>" [0 0 0] with "deagle"
+            L 05/04/2020 - 21:50:04: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] killed "Dean<9><STEAM_1:1:00000004><TERRORIST>" [0 0 0] with "deagle"
+            L 05/04/2020 - 22:02:47: Game Over: deathmatch mg_deathmatch de_sugarcane score 0:0 after 13 min"#,
+        );
+
+        let bob = utils::player_id("Bob", "STEAM_1:0:00000002", &engine.config);
+        let dean = utils::player_id("Dean", "STEAM_1:1:00000004", &engine.config);
+        let james = utils::player_id("James", "STEAM_1:0:00000005", &engine.config);
+        assert_eq!(
+            engine.game_state().result(),
+            Some(&state::MatchResult::Ranking {
+                players: vec![bob, dean, james]
+            })
+        );
+    }
+
+    #[test]
+    fn test_custom_pattern_bad_timestamp() {
+        let mut config = config::Config::default();
+        config.custom_patterns = Some(vec![config::CustomPattern {
+            name: "clutch_won".to_owned(),
+            pattern: r#"^L \S+ - \S+: "(.+)<\d+>.*" (triggered) "clutch_won""#.to_owned(),
+            // Group 2 is the word "triggered", not a timestamp
+            timestamp: Some(2),
+            fields: None,
+        }]);
+        let mut engine = Engine::new(config);
+
+        let json = engine
+            .parse_line(
+                r#"L 05/04/2020 - 21:57:14: "Mark<7><STEAM_1:1:00000006><CT>" triggered "clutch_won""#,
+            )
+            .unwrap();
+        let event: serde_json::Value = serde_json::from_str(&json[0]).unwrap();
+
+        // The line's own timestamp is used instead
+        assert_eq!(event["epoch"], 1588629434);
+        assert_eq!(event["detail"]["event_type"], "clutch_won");
+    }
+
+    #[test]
+    fn test_damage_matrix() {
+        let mut engine = Engine::new(config::Config::default());
+
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane"
+            L 05/04/2020 - 21:49:18: "Bob<6><STEAM_1:0:00000002>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:49:18: "Alice<8><STEAM_1:1:00000001>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:49:18: "Dean<9><STEAM_1:1:00000004>" switched from team <Unassigned> to <TERRORIST>
+            L 05/04/2020 - 21:49:20: World triggered "Round_Start"
+            L 05/04/2020 - 21:50:01: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] attacked "Dean<9><STEAM_1:1:00000004><TERRORIST>" [0 0 0] with "deagle" (damage "51") (damage_armor "0") (health "49") (armor "0") (hitgroup "chest")
+            L 05/04/2020 - 21:50:02: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] attacked "Alice<8><STEAM_1:1:00000001><CT>" [0 0 0] with "deagle" (damage "12") (damage_armor "0") (health "88") (armor "0") (hitgroup "left leg")
+            L 05/04/2020 - 21:50:03: World triggered "Round_End"
+            L 05/04/2020 - 21:50:20: World triggered "Round_Start"
+            L 05/04/2020 - 21:51:01: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] attacked "Dean<9><STEAM_1:1:00000004><TERRORIST>" [0 0 0] with "ak47" (damage "27") (damage_armor "3") (health "73") (armor "97") (hitgroup "stomach")"#,
+        );
+
+        let bob = utils::player_id("Bob", "STEAM_1:0:00000002", &engine.config);
+        let alice = utils::player_id("Alice", "STEAM_1:1:00000001", &engine.config);
+        let dean = utils::player_id("Dean", "STEAM_1:1:00000004", &engine.config);
+
+        let damage = engine.game_state().damage();
+        let given = damage.enemy().between(&bob, &dean).unwrap();
+        assert_eq!((given.hits(), given.damage()), (2, 78));
+        assert_eq!(
+            damage.friendly().between(&bob, &alice).unwrap().damage(),
+            12
+        );
+
+        let rounds = engine.game_state().rounds();
+        assert_eq!(
+            rounds[0]
+                .damage()
+                .enemy()
+                .between(&bob, &dean)
+                .unwrap()
+                .damage(),
+            51
+        );
+        assert_eq!(
+            rounds[1]
+                .damage()
+                .enemy()
+                .between(&bob, &dean)
+                .unwrap()
+                .damage(),
+            27
+        );
+        assert!(rounds[1]
+            .damage()
+            .friendly()
+            .between(&bob, &alice)
+            .is_none());
+
+        // They're all in the game_over event
+        let json = engine
+            .parse_line(
+                "L 05/04/2020 - 22:02:47: Game Over: competitive mg_active de_sugarcane score 1:0 after 13 min",
+            )
+            .unwrap();
+        let event: serde_json::Value = serde_json::from_str(json.last().unwrap()).unwrap();
+        assert_eq!(event["detail"]["event_type"], "game_over");
+
+        let damage: serde_json::Value =
+            serde_json::from_str(event["detail"]["damage"].as_str().unwrap()).unwrap();
+        assert_eq!(damage["enemy"]["given"][&bob][&dean]["damage"], 78);
+
+        let round_damage: serde_json::Value =
+            serde_json::from_str(event["detail"]["round_damage"].as_str().unwrap()).unwrap();
+        assert_eq!(
+            round_damage["2"]["enemy"]["given"][&bob][&dean]["damage"],
+            27
+        );
+        assert_eq!(
+            round_damage["1"]["friendly"]["given"][&bob][&alice]["hits"],
+            1
+        );
+    }
+
+    const BOT_LINES: &str = r#"L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane"
+            L 05/04/2020 - 21:49:18: "Bob<6><STEAM_1:0:00000002>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:49:18: "Bert<11><BOT>" switched from team <Unassigned> to <TERRORIST>
+            L 05/04/2020 - 21:49:20: World triggered "Round_Start"
+            L 05/04/2020 - 21:50:01: "Bert<11><BOT><TERRORIST>" [0 0 0] attacked "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] with "glock" (damage "24") (damage_armor "0") (health "76") (armor "0") (hitgroup "chest")
+            L 05/04/2020 - 21:50:02: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] attacked "Bert<11><BOT><TERRORIST>" [0 0 0] with "deagle" (damage "100") (damage_armor "0") (health "0") (armor "0") (hitgroup "head")
+            L 05/04/2020 - 21:50:02: "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] killed "Bert<11><BOT><TERRORIST>" [0 0 0] with "deagle" (headshot)
+            L 05/04/2020 - 21:50:03: World triggered "Round_End"
+            L 05/04/2020 - 21:50:20: World triggered "Round_Start"
+            L 05/04/2020 - 21:51:01: "Bert<14><BOT><TERRORIST>" [0 0 0] attacked "Bob<6><STEAM_1:0:00000002><CT>" [0 0 0] with "glock" (damage "30") (damage_armor "0") (health "70") (armor "0") (hitgroup "stomach")"#;
+
+    #[test]
+    fn test_excluded_bots() {
+        let mut engine = Engine::new(config::Config {
+            include_bots: Some(false),
+            ..config::Config::default()
+        });
+        parse_lines(&mut engine, BOT_LINES);
+
+        // Neither the bot's attacks nor the kill of the bot count for anything. Bob played the round, but that's all
+        let scoreboard = engine.game_state().scoreboard();
+        assert_eq!(scoreboard.len(), 1);
+        assert_eq!(
+            (
+                scoreboard[0].kills(),
+                scoreboard[0].deaths(),
+                scoreboard[0].damage()
+            ),
+            (0, 0, 0)
+        );
+        assert!(engine
+            .game_state()
+            .damage()
+            .enemy()
+            .given_by("BOT_Bert")
+            .is_empty());
+        assert!(engine
+            .game_state()
+            .damage()
+            .enemy()
+            .taken_by("BOT_Bert")
+            .is_empty());
+        assert!(engine.game_state().rounds()[0].kills().is_empty());
+
+        // Nor is the bot on a team
+        assert!(engine.game_state().ts().players().is_empty());
+        assert_eq!(engine.coverage().parsed_lines(), 10);
+    }
+
+    #[test]
+    fn test_bot_ids() {
+        let mut engine = Engine::new(config::Config::default());
+        parse_lines(&mut engine, BOT_LINES);
+
+        // The bot keeps its ID, going by its name, even though its userid changed
+        let bob = utils::player_id("Bob", "STEAM_1:0:00000002", &engine.config);
+        let taken = engine.game_state().damage().enemy().taken_by(&bob);
+        assert_eq!(taken.len(), 1);
+        let given = engine
+            .game_state()
+            .damage()
+            .enemy()
+            .between("BOT_Bert", &bob)
+            .unwrap();
+        assert_eq!((given.hits(), given.damage()), (2, 54));
+
+        let ids: Vec<&str> = engine
+            .game_state()
+            .scoreboard()
+            .iter()
+            .map(|score| score.player().id())
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&"BOT_Bert"));
+    }
+
+    #[test]
+    fn test_team_flashes() {
+        let mut engine = Engine::new(config::Config::default());
+        let json = |engine: &mut Engine, line: &str| -> serde_json::Value {
+            let json = engine.parse_line(line).unwrap();
+            serde_json::from_str(&json[0]).unwrap()
+        };
+
+        // An enemy, a teammate and the thrower themselves
+        let event = json(
+            &mut engine,
+            r#"L 05/04/2020 - 21:55:01: "James<3><STEAM_1:0:00000005><TERRORIST>" blinded for 3.68 by "Bob<6><STEAM_1:0:00000002><CT>" from flashbang entindex 478 "#,
+        );
+        assert_eq!(event["detail"]["team_flash"], "false");
+        assert_eq!(event["detail"]["entindex"], "478");
+
+        let event = json(
+            &mut engine,
+            r#"L 05/04/2020 - 21:55:01: "Alice<8><STEAM_1:1:00000001><CT>" blinded for 1.20 by "Bob<6><STEAM_1:0:00000002><CT>" from flashbang entindex 478 "#,
+        );
+        assert_eq!(event["detail"]["team_flash"], "true");
+
+        let event = json(
+            &mut engine,
+            r#"L 05/04/2020 - 21:55:01: "Bob<6><STEAM_1:0:00000002><CT>" blinded for 0.50 by "Bob<6><STEAM_1:0:00000002><CT>" from flashbang entindex 478 "#,
+        );
+        assert_eq!(event["detail"]["team_flash"], "true");
+        assert_eq!(event["detail"]["self_inflicted"], "true");
+    }
+
+    #[test]
+    fn test_game_over_map() {
+        let mut engine = Engine::new(config::Config::default());
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:49:12: World triggered "Match_Start" on "de_inferno"
+            L 05/04/2020 - 22:02:47: Game Over: competitive mg_active de_sugarcane score 1:0 after 13 min"#,
+        );
+
+        assert_eq!(engine.game_state().map().name(), "de_sugarcane");
+        assert_eq!(
+            engine.game_state().mode(),
+            Some(&state::GameMode::Competitive)
+        );
+    }
+
+    #[test]
+    fn test_game_over_bad_score() {
+        let mut engine = Engine::new(config::Config::default());
+        parse_lines(
+            &mut engine,
+            "L 05/04/2020 - 22:02:47: Game Over: competitive mg_active de_sugarcane score 99999999999:0 after 13 min",
+        );
+
+        assert_eq!(engine.game_state().phase(), state::MatchPhase::Ended);
+        assert!(engine.game_state().result().is_none());
+    }
+
+    #[test]
+    fn test_suicide_is_a_death() {
+        let mut engine = Engine::new(config::Config::default());
+        parse_lines(
+            &mut engine,
+            r#"L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane"
+            L 05/04/2020 - 21:49:18: "Bob<6><STEAM_1:0:00000002>" switched from team <Unassigned> to <CT>
+            L 05/04/2020 - 21:49:18: "James<3><STEAM_1:0:00000005>" switched from team <Unassigned> to <TERRORIST>
+            L 05/04/2020 - 21:49:20: World triggered "Round_Start"
+            L 05/04/2020 - 21:50:01: "James<3><STEAM_1:0:00000005><TERRORIST>" [-320 -2014 273] committed suicide with "world"
+            L 05/04/2020 - 21:50:03: World triggered "Round_End""#,
+        );
+
+        let scoreboard = engine.game_state().scoreboard();
+        let score_of = |name: &str, steam_id: &str| {
+            let id = utils::player_id(name, steam_id, &engine.config);
+            scoreboard
+                .iter()
+                .find(|score| score.player().id() == id)
+                .unwrap()
+        };
+
+        // James fell to their death, so didn't survive the round, while Bob did
+        let james = score_of("James", "STEAM_1:0:00000005");
+        assert_eq!((james.kills(), james.deaths()), (0, 1));
+        assert_eq!(james.kast(), 0.0);
+
+        let bob = score_of("Bob", "STEAM_1:0:00000002");
+        assert_eq!(bob.deaths(), 0);
+        assert_eq!(bob.kast(), 100.0);
+    }
+}
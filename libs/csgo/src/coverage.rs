@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// Reduce a log line to its general shape, so that lines which differ only by player names, numbers, positions, etc. are grouped together
+///
+/// ```text
+/// L 05/04/2020 - 21:49:33: "Alice<8><STEAM_1:1:00000001><CT>" purchased "item_kevlar"
+/// ```
+///
+/// becomes
+///
+/// ```text
+/// L <timestamp>: <player> purchased "*"
+/// ```
+///
+/// Player tuples become `<player>`, whether or not they're quoted, so each player doesn't get a shape of their own
+///
+/// Lists, such as the inventory a player left the buyzone with, become `[ * ]`
+///
+/// Trigger names are left as they are, as they're what distinguish one kind of line from another
+pub fn normalise(line: &str) -> String {
+    lazy_static! {
+        static ref TIMESTAMP: regex::Regex =
+            regex::Regex::new(r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}:"#).unwrap();
+        static ref PLAYER: regex::Regex = regex::Regex::new(
+            r#"(?:"[^"]*|[^\s"<>,]+)<\d+><(?:STEAM_[^<>]*|BOT|\[U:[^<>]*\])>(?:<[^<>]*>)?"?"#
+        )
+        .unwrap();
+        // e.g. the "Alice<8>," in an ACCOLADE line
+        static ref ACCOLADE_PLAYER: regex::Regex =
+            regex::Regex::new(r#"\t[^\t,]+<\d+>,"#).unwrap();
+        static ref QUOTED: regex::Regex = regex::Regex::new(r#"(triggered )?"[^"]*""#).unwrap();
+        static ref LIST: regex::Regex = regex::Regex::new(r#"\[ [^\]]* \]"#).unwrap();
+        static ref NUMBER: regex::Regex = regex::Regex::new(r#"-?\d+(?:\.\d+)?"#).unwrap();
+        static ref WHITESPACE: regex::Regex = regex::Regex::new(r#"\s+"#).unwrap();
+    }
+
+    let shape = TIMESTAMP.replace(line.trim(), "L <timestamp>:");
+    let shape = PLAYER.replace_all(&shape, "<player>");
+    let shape = ACCOLADE_PLAYER.replace_all(&shape, "\t<player>,");
+
+    // Triggers are what distinguish one kind of line from another, so they're kept
+    let shape = QUOTED.replace_all(&shape, |captures: &regex::Captures| {
+        if captures.get(1).is_some() {
+            captures[0].to_owned()
+        } else {
+            "\"*\"".to_owned()
+        }
+    });
+    let shape = LIST.replace_all(&shape, "[ * ]");
+    let shape = NUMBER.replace_all(&shape, "#");
+    let shape = WHITESPACE.replace_all(&shape, " ");
+
+    shape.into_owned()
+}
+
+/// How often a particular shape of unparsed line was seen, along with the first example of it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnparsedShape {
+    count: usize,
+    example: String,
+}
+
+/// Keeps count of which lines were understood by the parser, and which weren't
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Coverage {
+    total_lines: usize,
+    parsed_lines: usize,
+    ignored_lines: usize,
+    unparsed: HashMap<String, UnparsedShape>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    pub fn record_parsed(&mut self) {
+        self.total_lines += 1;
+        self.parsed_lines += 1;
+    }
+
+    /// Count a line the parser recognised but deliberately does nothing with, e.g. a chicken being killed.
+    /// These don't count as parsed, so they don't flatter the coverage
+    pub fn record_ignored(&mut self) {
+        self.total_lines += 1;
+        self.ignored_lines += 1;
+    }
+
+    /// Count the unparsed line against its shape, returning the shape
+    pub fn record_unparsed(&mut self, line: &str) -> String {
+        self.total_lines += 1;
+
+        let shape = normalise(line);
+
+        self.unparsed
+            .entry(shape.clone())
+            .or_insert_with(|| UnparsedShape {
+                count: 0,
+                example: line.to_owned(),
+            })
+            .count += 1;
+
+        shape
+    }
+
+    pub fn total_lines(&self) -> usize {
+        self.total_lines
+    }
+
+    pub fn parsed_lines(&self) -> usize {
+        self.parsed_lines
+    }
+
+    pub fn ignored_lines(&self) -> usize {
+        self.ignored_lines
+    }
+
+    /// The percentage of lines that were understood by the parser
+    pub fn percentage(&self) -> f64 {
+        if self.total_lines == 0 {
+            100.0
+        } else {
+            self.parsed_lines as f64 * 100.0 / self.total_lines as f64
+        }
+    }
+}
+
+impl std::fmt::Display for Coverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Parsed {} of {} lines ({:.1}%)",
+            self.parsed_lines,
+            self.total_lines,
+            self.percentage()
+        )?;
+
+        if self.ignored_lines > 0 {
+            writeln!(f, "Recognised but ignored {} lines", self.ignored_lines)?;
+        }
+
+        if !self.unparsed.is_empty() {
+            writeln!(f, "Unparsed line shapes:")?;
+
+            // Most common first, so new log formats stand out
+            let mut shapes: Vec<_> = self.unparsed.iter().collect();
+            shapes.sort_by(|(a_shape, a), (b_shape, b)| {
+                b.count.cmp(&a.count).then_with(|| a_shape.cmp(b_shape))
+            });
+
+            for (shape, unparsed) in shapes {
+                writeln!(f, "{:>8}  {}", unparsed.count, shape)?;
+                writeln!(f, "{:>8}  e.g. {}", "", unparsed.example)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise() {
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:49:33: "Alice<8><STEAM_1:1:00000001><CT>" purchased "item_kevlar""#
+            ),
+            r#"L <timestamp>: <player> purchased "*""#
+        );
+
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:55:12: Molotov projectile spawned at -602.358887 696.281433 581.419556, velocity 199.928925 -605.474182 227.317413"#
+            ),
+            r#"L <timestamp>: Molotov projectile spawned at # # #, velocity # # #"#
+        );
+
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:53:04: "Sam<5><STEAM_1:0:00000003><TERRORIST>" left buyzone with [ weapon_knife_t weapon_glock weapon_sg556 kevlar(100) helmet ]"#
+            ),
+            r#"L <timestamp>: <player> left buyzone with [ * ]"#
+        );
+
+        // The opening quote is missing from this one
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:49:14: James<3><STEAM_1:0:00000005><>" entered the game"#
+            ),
+            r#"L <timestamp>: <player> entered the game"#
+        );
+
+        // Two players, neither of whom should show up in the shape
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:50:10: "Dean<9><STEAM_1:1:00000004><TERRORIST>" assisted killing "Bob<6><STEAM_1:0:00000002><CT>""#
+            ),
+            normalise(
+                r#"L 05/04/2020 - 21:52:43: "Bob<6><STEAM_1:0:00000002><CT>" assisted killing "James<3><STEAM_1:0:00000005><TERRORIST>""#
+            ),
+        );
+        assert_eq!(
+            normalise(
+                r#"L 05/04/2020 - 21:50:10: "Dean<9><STEAM_1:1:00000004><TERRORIST>" assisted killing "Bob<6><STEAM_1:0:00000002><CT>""#
+            ),
+            r#"L <timestamp>: <player> assisted killing <player>"#
+        );
+
+        assert_eq!(
+            normalise(
+                "L 05/04/2020 - 22:02:47: ACCOLADE, FINAL: {hsp},\tDean<1>,\tVALUE: 55.555557,\tPOS: 1,\tSCORE: 35.432098"
+            ),
+            normalise(
+                "L 05/04/2020 - 22:02:47: ACCOLADE, FINAL: {hsp},\tBob<5>,\tVALUE: 33.333332,\tPOS: 1,\tSCORE: 18.148148"
+            ),
+        );
+    }
+
+    #[test]
+    fn test_coverage() {
+        let mut coverage = Coverage::new();
+        coverage.record_parsed();
+        coverage.record_ignored();
+        coverage.record_unparsed(r#"L 05/04/2020 - 21:49:33: "Alice<8><STEAM_1:1:00000001><CT>" purchased "item_kevlar""#);
+        coverage.record_unparsed(r#"L 05/04/2020 - 21:49:34: "Dean<9><STEAM_1:1:00000004><TERRORIST>" purchased "deagle""#);
+        coverage.record_unparsed(r#"L 05/04/2020 - 21:49:15: World triggered "Round_Start""#);
+
+        assert_eq!(coverage.total_lines(), 5);
+        assert_eq!(coverage.parsed_lines(), 1);
+        assert_eq!(coverage.ignored_lines(), 1);
+        assert_eq!(coverage.unparsed.len(), 2);
+        assert!(coverage
+            .unparsed
+            .contains_key(r#"L <timestamp>: World triggered "Round_Start""#));
+        assert_eq!(
+            coverage.unparsed[r#"L <timestamp>: <player> purchased "*""#].count,
+            2
+        );
+    }
+}
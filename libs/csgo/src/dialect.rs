@@ -0,0 +1,369 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+/// The game whose log format is being read
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Dialect {
+    CsGo,
+    Cs2,
+}
+
+impl Dialect {
+    pub fn name(&self) -> &str {
+        match self {
+            Dialect::CsGo => "csgo",
+            Dialect::Cs2 => "cs2",
+        }
+    }
+}
+
+/// Works out which dialect the logs are written in, and rewrites lines from either dialect into the CS:GO form that the `rgx` patterns understand
+///
+/// The CS2 differences handled here are:
+/// - Steam IDs in player tuples are written as `[U:1:<account id>]` rather than `STEAM_1:<y>:<z>`, so they're converted back (so Steam ID translation and player identity work the same for both)
+/// - Player tuples sometimes use `<T>` as the team tag rather than `<TERRORIST>`
+/// - Round stats are written as a multi-line JSON-ish block between `JSON_BEGIN{` and `}}JSON_END`, which is collected up and emitted as a single `round_stats` line once complete
+pub struct Normaliser {
+    dialect: Option<Dialect>,
+    json_block: Option<JsonBlock>,
+}
+
+/// The most lines a JSON block can have before it's assumed that its `}}JSON_END` went missing.
+/// A `round_stats` block for a full 5v5 is around 20 lines
+const MAX_JSON_BLOCK_LINES: usize = 256;
+
+/// A JSON block that's still being read in
+struct JsonBlock {
+    timestamp: String,
+    lines: Vec<String>,
+}
+
+impl Normaliser {
+    pub fn new() -> Normaliser {
+        Normaliser {
+            dialect: None,
+            json_block: None,
+        }
+    }
+
+    /// The dialect detected so far, assuming CS:GO until there's evidence otherwise
+    pub fn dialect(&self) -> Dialect {
+        self.dialect.unwrap_or(Dialect::CsGo)
+    }
+
+    /// Rewrite the line into the CS:GO form, if necessary.
+    ///
+    /// Returns `None` if the line is part of a JSON block that isn't finished yet
+    pub fn normalise<'a>(&mut self, line: &'a str) -> Option<Cow<'a, str>> {
+        lazy_static! {
+            static ref LOG_FILE_STARTED: regex::Regex = regex::Regex::new(
+                r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}: Log file started .*\(game "([^"]*)"\)"#
+            )
+            .unwrap();
+            static ref JSON_LINE: regex::Regex =
+                regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): (.*)$"#).unwrap();
+            static ref U_STEAM_ID: regex::Regex = regex::Regex::new(r#"<\[U:1:(\d+)\]>"#).unwrap();
+            static ref T_TEAM: regex::Regex =
+                regex::Regex::new(r#"(<(?:\[U:1:\d+\]|STEAM_[^<>]+|BOT)>)<T>""#).unwrap();
+            // What the lines inside a JSON block look like, e.g. `"score_t" : "0",` or `},`
+            static ref JSON_CONTENT: regex::Regex =
+                regex::Regex::new(r#"^\s*(?:"[^"]*"\s*:|[{}\[\]]|$)"#).unwrap();
+        }
+
+        // Are we in the middle of a JSON block?
+        if let Some(block) = self.json_block.as_mut() {
+            let (content, event_shaped) = match JSON_LINE.captures(line) {
+                Some(captures) => (captures[2].to_owned(), !JSON_CONTENT.is_match(&captures[2])),
+                None => (line.to_owned(), false),
+            };
+
+            if content.trim_end().ends_with("}}JSON_END") {
+                let block = self.json_block.take().unwrap();
+                return Some(Cow::Owned(block.into_line()));
+            }
+
+            if event_shaped {
+                // The block never got its `}}JSON_END`, so give up on it rather than swallowing the rest of the log
+                log::warn!(
+                    "Dropping unfinished JSON block from {} at: {}",
+                    block.timestamp,
+                    line
+                );
+                self.json_block = None;
+            } else if block.lines.len() >= MAX_JSON_BLOCK_LINES {
+                log::warn!(
+                    "Dropping JSON block from {} as it's longer than {} lines",
+                    block.timestamp,
+                    MAX_JSON_BLOCK_LINES
+                );
+                self.json_block = None;
+                return None;
+            } else {
+                block.lines.push(content);
+                return None;
+            }
+        }
+
+        if let Some(captures) = JSON_LINE.captures(line) {
+            if captures[2].trim_end() == "JSON_BEGIN{" {
+                // Only CS2 writes these
+                self.detect(Dialect::Cs2);
+
+                self.json_block = Some(JsonBlock {
+                    timestamp: captures[1].to_owned(),
+                    lines: Vec::new(),
+                });
+                return None;
+            }
+        }
+
+        if let Some(captures) = LOG_FILE_STARTED.captures(line) {
+            // CS2 dedicated servers run from a `game/csgo` directory, where CS:GO ran straight from `csgo`
+            let game_dir = captures[1].trim_end_matches('/');
+            if game_dir.ends_with("game/csgo") {
+                self.detect(Dialect::Cs2);
+            } else {
+                self.detect(Dialect::CsGo);
+            }
+        }
+
+        let mut normalised = Cow::Borrowed(line);
+
+        if U_STEAM_ID.is_match(&normalised) {
+            self.detect(Dialect::Cs2);
+
+            normalised = Cow::Owned(
+                U_STEAM_ID
+                    .replace_all(&normalised, |captures: &regex::Captures| {
+                        let account_id = captures[1].parse::<u64>().unwrap_or_default();
+                        format!("<STEAM_1:{}:{}>", account_id % 2, account_id / 2)
+                    })
+                    .into_owned(),
+            );
+        } else if normalised.contains("<STEAM_") {
+            self.detect(Dialect::CsGo);
+        }
+
+        if T_TEAM.is_match(&normalised) {
+            normalised = Cow::Owned(
+                T_TEAM
+                    .replace_all(&normalised, "${1}<TERRORIST>\"")
+                    .into_owned(),
+            );
+        }
+
+        Some(normalised)
+    }
+
+    fn detect(&mut self, dialect: Dialect) {
+        if self.dialect != Some(dialect) {
+            log::info!("Detected {} log format", dialect.name());
+            self.dialect = Some(dialect);
+        }
+    }
+}
+
+/// Pair up each of the players in a CS2 round stats block with the stat names from its `fields` entry, e.g.
+///
+/// ```text
+/// "fields" : "accountid, team, money", "players" : { "player_0" : "3, 3, 3200" }
+/// ```
+///
+/// becomes `[{"accountid": "3", "team": "3", "money": "3200"}]`
+pub fn round_stats_players(round_stats: &serde_json::Value) -> Vec<HashMap<String, String>> {
+    let fields: Vec<&str> = round_stats["fields"]
+        .as_str()
+        .unwrap_or_default()
+        .split(',')
+        .map(|field| field.trim())
+        .collect();
+
+    let mut players = Vec::new();
+
+    if let Some(player_map) = round_stats["players"].as_object() {
+        // Keep the players in the order they were written
+        let mut keys: Vec<&String> = player_map.keys().collect();
+        keys.sort_by_key(|key| {
+            key.trim_start_matches("player_")
+                .parse::<usize>()
+                .unwrap_or_default()
+        });
+
+        for key in keys {
+            let values = player_map[key].as_str().unwrap_or_default().split(',');
+
+            let player = fields
+                .iter()
+                .zip(values)
+                .map(|(field, value)| (field.to_string(), value.trim().to_owned()))
+                .collect();
+
+            players.push(player);
+        }
+    }
+
+    players
+}
+
+impl JsonBlock {
+    /// Turn the collected block into a single line of JSON, e.g. `L 10/01/2023 - 16:47:00: JSON_BLOCK {"name":"round_stats",...}`
+    fn into_line(self) -> String {
+        lazy_static! {
+            static ref TRAILING_COMMA: regex::Regex = regex::Regex::new(r#",\s*}"#).unwrap();
+        }
+
+        // The block's opening brace is on the JSON_BEGIN line, and the closing braces are on the JSON_END line
+        let json = format!("{{{}}}}}", self.lines.join(""));
+        let json = TRAILING_COMMA.replace_all(&json, "}");
+
+        // Re-serialise it so that it's definitely on one line
+        let json = match serde_json::from_str::<serde_json::Value>(&json) {
+            Ok(value) => value.to_string(),
+            Err(why) => {
+                log::warn!("Failed to parse JSON block: {}", why);
+                json.into_owned()
+            }
+        };
+
+        format!("L {}: JSON_BLOCK {}", self.timestamp, json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSGO_FIXTURE: &str = r#"L 05/04/2020 - 21:49:05: "Alice<8><STEAM_1:1:00000001><>" entered the game
+L 05/04/2020 - 21:49:14: World triggered "Match_Start" on "de_sugarcane"
+L 05/04/2020 - 21:49:18: "Alice<8><STEAM_1:1:00000001>" switched from team <Unassigned> to <CT>
+L 05/04/2020 - 21:50:03: "Bob<6><STEAM_1:0:00000002><CT>" [-1123 -1020 442] killed "Dean<9><STEAM_1:1:00000004><TERRORIST>" [-1158 -565 576] with "deagle"
+L 05/04/2020 - 21:55:01: "James<3><STEAM_1:0:00000005><TERRORIST>" blinded for 3.68 by "Bob<6><STEAM_1:0:00000002><CT>" from flashbang entindex 478 "#;
+
+    const CS2_FIXTURE: &str = r#"L 10/01/2023 - 16:45:32: Log file started (file "logs/2023_10_01_164532.log") (game "/home/steam/cs2/game/csgo") (version "9999")
+L 10/01/2023 - 16:46:00: World triggered "Match_Start" on "de_ancient"
+L 10/01/2023 - 16:46:02: "Alice<2><[U:1:3]>" switched from team <Unassigned> to <CT>
+L 10/01/2023 - 16:46:11: "Alice<2><[U:1:3]><CT>" [-410 1200 -120] killed "Bob<3><[U:1:4]><T>" [-320 1410 -110] with "m4a1_silencer" (headshot)
+L 10/01/2023 - 16:46:12: "Bob<3><[U:1:4]><TERRORIST>" blinded for 2.34 by "Alice<2><[U:1:3]><CT>" from flashbang entindex 254
+L 10/01/2023 - 16:47:00: JSON_BEGIN{
+L 10/01/2023 - 16:47:00: "name": "round_stats",
+L 10/01/2023 - 16:47:00: "round_number" : "1",
+L 10/01/2023 - 16:47:00: "score_t" : "0",
+L 10/01/2023 - 16:47:00: "score_ct" : "1",
+L 10/01/2023 - 16:47:00: "map" : "de_ancient",
+L 10/01/2023 - 16:47:00: "fields" : "             accountid,   team,  money,  kills, deaths",
+L 10/01/2023 - 16:47:00: "players" : {
+L 10/01/2023 - 16:47:00: "player_0" : "             3,      3,   3200,      1,      0",
+L 10/01/2023 - 16:47:00: "player_1" : "             4,      2,   1900,      0,      1"
+L 10/01/2023 - 16:47:00: }}JSON_END"#;
+
+    fn normalise_all(fixture: &str) -> (Normaliser, Vec<String>) {
+        let mut normaliser = Normaliser::new();
+        let lines = fixture
+            .lines()
+            .filter_map(|line| normaliser.normalise(line).map(|line| line.into_owned()))
+            .collect();
+
+        (normaliser, lines)
+    }
+
+    #[test]
+    fn test_csgo_fixture() {
+        let (normaliser, lines) = normalise_all(CSGO_FIXTURE);
+
+        assert_eq!(normaliser.dialect, Some(Dialect::CsGo));
+
+        // CS:GO lines are left exactly as they are
+        assert_eq!(lines, CSGO_FIXTURE.lines().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_cs2_fixture() {
+        let (normaliser, lines) = normalise_all(CS2_FIXTURE);
+
+        assert_eq!(normaliser.dialect(), Dialect::Cs2);
+        assert_eq!(lines.len(), 6);
+
+        assert_eq!(
+            lines[2],
+            r#"L 10/01/2023 - 16:46:02: "Alice<2><STEAM_1:1:1>" switched from team <Unassigned> to <CT>"#
+        );
+        assert_eq!(
+            lines[3],
+            r#"L 10/01/2023 - 16:46:11: "Alice<2><STEAM_1:1:1><CT>" [-410 1200 -120] killed "Bob<3><STEAM_1:0:2><TERRORIST>" [-320 1410 -110] with "m4a1_silencer" (headshot)"#
+        );
+        assert!(crate::rgx::switched_team(&lines[2]).is_some());
+        assert!(crate::rgx::kill(&lines[3]).is_some());
+        assert!(crate::rgx::blinded(&lines[4]).is_some());
+
+        // The JSON block comes out as one line
+        let captures = crate::rgx::json_block(&lines[5]).unwrap();
+        assert_eq!(&captures[1], "10/01/2023 - 16:47:00");
+        let json: serde_json::Value = serde_json::from_str(&captures[2]).unwrap();
+        assert_eq!(json["name"], "round_stats");
+        assert_eq!(json["round_number"], "1");
+        assert_eq!(
+            json["players"]["player_1"],
+            "             4,      2,   1900,      0,      1"
+        );
+    }
+
+    #[test]
+    fn test_round_stats_players() {
+        let (_, lines) = normalise_all(CS2_FIXTURE);
+        let captures = crate::rgx::json_block(&lines[5]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&captures[2]).unwrap();
+
+        let players = round_stats_players(&json);
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[0]["accountid"], "3");
+        assert_eq!(players[0]["money"], "3200");
+        assert_eq!(players[1]["deaths"], "1");
+    }
+
+    #[test]
+    fn test_detection_from_steam_ids() {
+        let mut normaliser = Normaliser::new();
+        assert_eq!(normaliser.dialect, None);
+
+        normaliser
+            .normalise(r#"L 10/01/2023 - 16:46:12: "Bob<3><[U:1:4]><TERRORIST>" purchased "ak47""#);
+        assert_eq!(normaliser.dialect, Some(Dialect::Cs2));
+    }
+
+    #[test]
+    fn test_unfinished_json_block() {
+        // The block is cut off by an ordinary event, which still gets through
+        let fixture = r#"L 10/01/2023 - 16:47:00: JSON_BEGIN{
+L 10/01/2023 - 16:47:00: "name": "round_stats",
+L 10/01/2023 - 16:47:00: "round_number" : "1",
+L 10/01/2023 - 16:47:05: World triggered "Round_Start"
+L 10/01/2023 - 16:47:06: "Alice<2><[U:1:3]><CT>" purchased "ak47""#;
+        let (_, lines) = normalise_all(fixture);
+        assert_eq!(
+            lines,
+            vec![
+                r#"L 10/01/2023 - 16:47:05: World triggered "Round_Start""#,
+                r#"L 10/01/2023 - 16:47:06: "Alice<2><STEAM_1:1:1><CT>" purchased "ak47""#,
+            ]
+        );
+
+        // A block that never ends is dropped once it's too long
+        let mut normaliser = Normaliser::new();
+        assert!(normaliser
+            .normalise("L 10/01/2023 - 16:47:00: JSON_BEGIN{")
+            .is_none());
+        for _ in 0..MAX_JSON_BLOCK_LINES {
+            assert!(normaliser
+                .normalise(r#"L 10/01/2023 - 16:47:00: "player_0" : "1, 2, 3","#)
+                .is_none());
+        }
+        assert!(normaliser.json_block.is_some());
+        assert!(normaliser
+            .normalise(r#"L 10/01/2023 - 16:47:00: "player_0" : "1, 2, 3","#)
+            .is_none());
+        assert!(normaliser.json_block.is_none());
+    }
+}
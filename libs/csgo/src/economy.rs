@@ -0,0 +1,157 @@
+/// The default `mp_startmoney`
+pub const START_MONEY: i64 = 800;
+
+/// The default `mp_maxmoney`
+pub const MAX_MONEY: i64 = 16000;
+
+/// Strip the prefixes the logs use for items in some places but not others, e.g. "weapon_deagle" and "item_kevlar" become "deagle" and "kevlar"
+pub fn item_name(item: &str) -> &str {
+    item.trim_start_matches("weapon_")
+        .trim_start_matches("item_")
+}
+
+/// The price of an item, as named in a purchase line
+pub fn price(item: &str) -> Option<i64> {
+    let price = match item_name(item) {
+        // Pistols
+        "glock" | "hkp2000" | "usp_silencer" => 200,
+        "p250" => 300,
+        "elite" => 400,
+        "fiveseven" | "tec9" | "cz75a" => 500,
+        "revolver" => 600,
+        "deagle" => 700,
+
+        // SMGs
+        "mac10" => 1050,
+        "ump45" => 1200,
+        "mp9" => 1250,
+        "bizon" => 1400,
+        "mp7" | "mp5sd" => 1500,
+        "p90" => 2350,
+
+        // Heavy
+        "nova" => 1050,
+        "sawedoff" => 1100,
+        "mag7" => 1300,
+        "negev" => 1700,
+        "xm1014" => 2000,
+        "m249" => 5200,
+
+        // Rifles
+        "galilar" => 1800,
+        "ssg08" => 1700,
+        "famas" => 2050,
+        "ak47" => 2700,
+        "m4a1_silencer" => 2900,
+        "sg556" => 3000,
+        "m4a1" => 3100,
+        "aug" => 3300,
+        "awp" => 4750,
+        "g3sg1" | "scar20" => 5000,
+
+        // Gear
+        "taser" => 200,
+        "defuser" => 400,
+        "kevlar" | "vest" => 650,
+        "assaultsuit" | "vesthelm" => 1000,
+
+        // Grenades
+        "decoy" => 50,
+        "flashbang" => 200,
+        "hegrenade" | "smokegrenade" => 300,
+        "molotov" => 400,
+        "incgrenade" => 600,
+
+        _ => return None,
+    };
+
+    Some(price)
+}
+
+/// The grenades, as named in `threw` lines and inventories
+pub fn is_grenade(item: &str) -> bool {
+    matches!(
+        item_name(item),
+        "flashbang" | "hegrenade" | "smokegrenade" | "molotov" | "incgrenade" | "decoy"
+    )
+}
+
+/// Whether the weapon in an attack line is a grenade doing damage. Molotovs and incendiaries burn as "inferno"
+pub fn is_utility_damage(weapon: &str) -> bool {
+    matches!(weapon, "hegrenade" | "inferno" | "molotov" | "incgrenade")
+}
+
+/// The reward for killing an enemy with the given weapon, in competitive mode
+pub fn kill_reward(weapon: &str) -> i64 {
+    match item_name(weapon) {
+        "awp" => 100,
+        "taser" => 0,
+        "p90" => 300,
+        "mac10" | "mp9" | "mp7" | "mp5sd" | "ump45" | "bizon" => 600,
+        "nova" | "sawedoff" | "mag7" | "xm1014" => 900,
+        weapon if weapon.starts_with("knife") || weapon == "bayonet" => 1500,
+        _ => 300,
+    }
+}
+
+/// The penalty for killing a teammate
+pub const TEAM_KILL_PENALTY: i64 = -300;
+
+/// The most losses the loss bonus counts, at which point it's as big as it gets
+pub const MAX_LOSS_STREAK: u32 = 5;
+
+/// The money each player on a team gets at the end of a round, given why the round was won and how many rounds in a row the team has now lost
+pub fn round_reward(won: bool, reason: &str, loss_streak: u32) -> i64 {
+    if won {
+        match reason {
+            "Target_Bombed" | "Bomb_Defused" => 3500,
+            _ => 3250,
+        }
+    } else {
+        (1400 + 500 * i64::from(loss_streak.saturating_sub(1))).min(3400)
+    }
+}
+
+/// What a player spawns with on each side, having not survived the previous round
+pub fn default_weapons(side: &str) -> Vec<String> {
+    let weapons: &[&str] = if side == "CT" {
+        &["knife", "hkp2000"]
+    } else {
+        &["knife_t", "glock"]
+    };
+
+    weapons.iter().map(|weapon| weapon.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prices() {
+        assert_eq!(price("item_kevlar"), Some(650));
+        assert_eq!(price("item_assaultsuit"), Some(1000));
+        assert_eq!(price("deagle"), Some(700));
+        assert_eq!(price("weapon_ak47"), Some(2700));
+        assert_eq!(price("something_new"), None);
+
+        assert!(is_grenade("weapon_flashbang"));
+        assert!(!is_grenade("weapon_ak47"));
+        assert!(is_utility_damage("inferno"));
+        assert!(!is_utility_damage("smokegrenade"));
+    }
+
+    #[test]
+    fn test_rewards() {
+        assert_eq!(kill_reward("knife_t"), 1500);
+        assert_eq!(kill_reward("awp"), 100);
+        assert_eq!(kill_reward("ak47"), 300);
+        assert_eq!(kill_reward("mp9"), 600);
+
+        assert_eq!(round_reward(true, "Terrorists_Win", 0), 3250);
+        assert_eq!(round_reward(true, "Target_Bombed", 0), 3500);
+        assert_eq!(round_reward(false, "CTs_Win", 1), 1400);
+        assert_eq!(round_reward(false, "CTs_Win", 3), 2400);
+        assert_eq!(round_reward(false, "CTs_Win", 9), 3400);
+    }
+}
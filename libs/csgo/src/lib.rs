@@ -4,9 +4,18 @@ pub mod cli;
 /// The config module provides reading/writing of config files
 pub mod config;
 
+/// The coverage module keeps track of which log lines the parser understood, so that unfamiliar log formats don't go unnoticed
+pub mod coverage;
+
 /// The core module handles the engine that drives the log reader and retrieves the parsed JSON
 pub mod core;
 
+/// The dialect module detects whether the logs were written by CS:GO or CS2, and normalises them into the same format
+mod dialect;
+
+/// The economy module knows the prices of items and the money rewards, for estimating how much money players have
+mod economy;
+
 /// The logs module handles locating and reading from CS:GO server log files
 mod logs;
 
@@ -16,8 +25,8 @@ mod rgx;
 /// The geo module handles map-related functions, such as distance calculations, and conversions between the in-game coordinate system and decimal degrees
 mod geo;
 
-/// The state module handles tracking of the game state for a given match
-mod state;
+/// The state module handles tracking of the game state for a given match, such as the teams, the rounds and the events in each of them
+pub mod state;
 
 /// The utils module provides various utility functions such as Steam ID translation
 mod utils;
@@ -19,9 +19,32 @@ impl Reader {
         }
     }
 
+    /// All the log files in the log directory, oldest first (based on file name)
+    pub fn log_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = match std::fs::read_dir(&self.log_dir_path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect(),
+            Err(why) => {
+                log::error!(
+                    "Failed to read log directory {}: {}",
+                    self.log_dir_path.display(),
+                    why
+                );
+                Vec::new()
+            }
+        };
+
+        files.sort(); // This sorts ascending
+
+        files
+    }
+
     fn latest_log_file(log_dir_path: &Path) -> Option<PathBuf> {
         // Find the most recent file in the directory (based on file name)
-        let mut files: Vec<_> = std::fs::read_dir(&log_dir_path)
+        let mut files: Vec<_> = std::fs::read_dir(log_dir_path)
             .unwrap()
             .map(|r| r.unwrap())
             .collect();
@@ -35,13 +58,13 @@ impl Reader {
 
         log::trace!(
             "Files in directory: '{}'",
-            std::fs::canonicalize(&log_dir_path).unwrap().display()
+            std::fs::canonicalize(log_dir_path).unwrap().display()
         );
         for file in &files {
             log::trace!("--> '{}'", file.path().display())
         }
 
-        let full_path = std::fs::canonicalize(&files[0].path()).unwrap(); //.display();
+        let full_path = std::fs::canonicalize(files[0].path()).unwrap(); //.display();
         Some(full_path)
     }
 
@@ -1,104 +1,832 @@
+use std::collections::HashMap;
+
 use lazy_static::lazy_static;
 
-//const MATCH_START: &str = r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}: World triggered "Match_Start" on "([^"]+)""#;
-//const GAME_OVER: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Game Over: ([^\s]+).+?score (\d+):(\d+) after (\d+) min"#;
-//const SWITCHED_TEAM: &str = r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}: "([^<]+)<\d{1,3}><(STEAM[^>]+)>" switched from team <(?:Unassigned|CT|TERRORIST)> to <(CT|TERRORIST)>"#;
-//const ATTACK: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] attacked "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)" \(damage "(\d+)"\) \(damage_armor "(\d+)"\) \(health "(\d+)"\) \(armor "(\d+)"\) \(hitgroup "([^"]+)"\)"#;
-//const KILL: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] killed "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)"(?: \(([^)]+)\))?"#;
-//const ASSIST: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" assisted killing "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>""#;
-//const SUICIDE: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[-?\d{1,5} -?\d{1,5} -?\d{1,5}\] committed suicide with "[a-zA-Z0-9]{1,20}""#;
-//const BLINDED: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" blinded for (\d\.\d{2}) by "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" from flashbang entindex [0-9\s]{1,5}"#;
-//const BOMB: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" triggered "(Got_The_Bomb|Dropped_The_Bomb|Planted_The_Bomb|Begin_Bomb_Defuse_With(?:out)?_Kit|Defused_The_Bomb)""#;
-//const HOSTAGE: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" triggered "(Touched_A_Hostage|Rescued_A_Hostage|Killed_A_Hostage)""#;
-//const CHICKEN: &str = r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" .+? killed other "chicken.+? with "([^"]+)""#;
+use super::config;
+
+/// The player tuple that appears in most log lines, e.g. "Alice<8><STEAM_1:1:00000001><CT>"
+///
+/// Players can (and do) put '<', '>' and '"' in their names, so rather than trying to describe the name, we capture the whole tuple and anchor on the fields at the end of it.
+/// The name is matched lazily, so the tuple ends at the first closing `"` after which the rest of the line still matches, rather than the last one. Otherwise a name that looks like a tuple further along the line would swallow everything before it.
+/// Use `player()` to split the captured tuple into its fields.
+macro_rules! player {
+    () => {
+        r#""(.+?<\d+><(?:STEAM_[^<>]+|BOT)><(?:CT|TERRORIST)>)""#
+    };
+}
+
+/// As per `player!`, but for lines where the player's team isn't shown, e.g. "Alice<8><STEAM_1:1:00000001>"
+macro_rules! player_without_team {
+    () => {
+        r#""(.+?<\d+><(?:STEAM_[^<>]+|BOT)>)""#
+    };
+}
+
+/// As per `player!`, but for lines where the player may not be on a team yet, e.g. "Alice<8><STEAM_1:1:00000001><Unassigned>"
+macro_rules! player_any_team {
+    () => {
+        r#""(.+?<\d+><(?:STEAM_[^<>]+|BOT)><[^<>]*>)""#
+    };
+}
+
+/// The fields of a player tuple
+#[derive(Debug, PartialEq)]
+pub struct PlayerTuple<'a> {
+    pub name: &'a str,
+    pub userid: &'a str,
+    pub steam_id: &'a str,
+    pub team: Option<&'a str>,
+}
+
+/// Split the last "<field>" off the end of the input, returning what's left and the contents of the field
+fn split_last_field(input: &str) -> Option<(&str, &str)> {
+    let without_bracket = input.strip_suffix('>')?;
+    let start = without_bracket.rfind('<')?;
+
+    Some((&input[..start], &without_bracket[start + 1..]))
+}
+
+fn is_steam_id_field(field: &str) -> bool {
+    field.starts_with("STEAM_") || field == "BOT"
+}
+
+/// Parse a player tuple such as "Alice<8><STEAM_1:1:00000001><CT>" (without the surrounding quotes).
+///
+/// The tuple is read from the right (team, Steam ID, userid) so that whatever is left over is the name, no matter what characters it contains
+pub fn player(tuple: &str) -> PlayerTuple<'_> {
+    let (rest, last) = split_last_field(tuple).unwrap_or((tuple, ""));
+
+    // The team is optional, so if the last field is a Steam ID then there isn't one
+    let (rest, steam_id, team) = if is_steam_id_field(last) {
+        (rest, last, None)
+    } else {
+        let (rest, steam_id) = split_last_field(rest).unwrap_or((rest, ""));
+        (rest, steam_id, Some(last))
+    };
+
+    let (name, userid) = split_last_field(rest).unwrap_or((rest, ""));
+
+    PlayerTuple {
+        name,
+        userid,
+        steam_id,
+        team,
+    }
+}
+
+/// Any line beginning with a timestamp
+pub fn timestamp(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref TIMESTAMP: regex::Regex =
+            regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}):"#).unwrap();
+    }
+
+    TIMESTAMP.captures(input)
+}
 
 /// A match start message can appear many times in the logs (e.g. at the start of the warm-up), but a proper "match" spans from the last match start message until the first game over message.
 /// This means we'll need to reset the game state on each match_start message, to avoid polluting the stats with stuff that happened during the warmup or other times outside of core gameplay.
-/// Awkwardly, this means that any switched_team events that occured before the last match_start event will be forgotten, so we'll need to check our knowledge of which players are in which teams on each event containing player/team info.
-pub fn match_start(input: &str) -> Option<regex::Captures> {
+/// The teams are carried over the reset, as the switched_team events that put players in them usually come before the last match_start event.
+pub fn match_start(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref MATCH_START: regex::Regex = regex::Regex::new(r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}: World triggered "Match_Start" on "([^"]+)""#).unwrap();
+        static ref MATCH_START: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): World triggered "Match_Start" on "([^"]+)""#).unwrap();
     }
 
     MATCH_START.captures(input)
 }
 
-pub fn game_over(input: &str) -> Option<regex::Captures> {
+/// The warmup starting or ending, or the game being restarted by `mp_restartgame`, which is logged as e.g. `Restart_Round_(3_seconds)`
+pub fn match_phase(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref MATCH_PHASE: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): World triggered "(Game_Commencing|Warmup_Start|Warmup_End|Restart_Round_\(\d+_seconds?\))""#).unwrap();
+    }
+
+    MATCH_PHASE.captures(input)
+}
+
+/// The end of a match, with the game mode, map group, map, score (CT first) and how long the match lasted
+pub fn game_over(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref GAME_OVER: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Game Over: ([^\s]+).+?score (\d+):(\d+) after (\d+) min"#).unwrap();
+        static ref GAME_OVER: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Game Over: (\S+) (\S*) (\S+) score (\d+):(\d+) after (\d+) min"#).unwrap();
     }
 
     GAME_OVER.captures(input)
 }
 
-pub fn switched_team(input: &str) -> Option<regex::Captures> {
+/// The clan name of the team playing on a side, which is written when the server has `mp_teamname_1` or `mp_teamname_2` set
+pub fn match_status_team(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref MATCH_STATUS_TEAM: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): MatchStatus: Team playing "(CT|TERRORIST)": (.*)$"#).unwrap();
+    }
+
+    MATCH_STATUS_TEAM.captures(input)
+}
+
+/// The score by side, as the server sees it, along with the map and how many rounds have been played
+pub fn match_status_score(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref MATCH_STATUS_SCORE: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): MatchStatus: Score: (\d+):(\d+) on map "([^"]+)" RoundsPlayed: (-?\d+)"#).unwrap();
+    }
+
+    MATCH_STATUS_SCORE.captures(input)
+}
+
+pub fn server_cvar(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref SERVER_CVAR: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): server_cvar: "([^"]+)" "([^"]*)""#
+        )
+        .unwrap();
+    }
+
+    SERVER_CVAR.captures(input)
+}
+
+pub fn rcon(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref RCON: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): rcon from "([^"]+)": command "(.*)""#
+        )
+        .unwrap();
+    }
+
+    RCON.captures(input)
+}
+
+pub fn loading_map(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref LOADING_MAP: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Loading map "([^"]+)""#
+        )
+        .unwrap();
+    }
+
+    LOADING_MAP.captures(input)
+}
+
+pub fn started_map(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref STARTED_MAP: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Started map "([^"]+)"(?: \(CRC "([^"]+)"\))?"#).unwrap();
+    }
+
+    STARTED_MAP.captures(input)
+}
+
+/// A multi-line JSON block (CS2 only), as put back together into a single line by the `dialect` module
+pub fn json_block(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref SWITCHED_TEAM: regex::Regex = regex::Regex::new(r#"^L \d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}: "([^<]+)<\d{1,3}><(STEAM[^>]+)>" switched from team <(?:Unassigned|CT|TERRORIST)> to <(CT|TERRORIST)>"#).unwrap();
+        static ref JSON_BLOCK: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): JSON_BLOCK (\{.*\})$"#
+        )
+        .unwrap();
+    }
+
+    JSON_BLOCK.captures(input)
+}
+
+pub fn switched_team(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref SWITCHED_TEAM: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player_without_team!(),
+            r#" switched from team <(Unassigned|CT|TERRORIST)> to <(CT|TERRORIST)>"#
+        ))
+        .unwrap();
     }
 
     SWITCHED_TEAM.captures(input)
 }
 
-pub fn attack(input: &str) -> Option<regex::Captures> {
+/// A player changing their name. The player tuple has the old name
+pub fn changed_name(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref CHANGED_NAME: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player_any_team!(),
+            r#" changed name to "(.*)"$"#
+        ))
+        .unwrap();
+    }
+
+    CHANGED_NAME.captures(input)
+}
+
+pub fn attack(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref ATTACK: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] attacked "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)" \(damage "(\d+)"\) \(damage_armor "(\d+)"\) \(health "(\d+)"\) \(armor "(\d+)"\) \(hitgroup "([^"]+)"\)"#).unwrap();
+        static ref ATTACK: regex::Regex = regex::Regex::new(concat!(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#, player!(), r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] attacked "#, player!(), r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)" \(damage "(\d+)"\) \(damage_armor "(\d+)"\) \(health "(\d+)"\) \(armor "(\d+)"\) \(hitgroup "([^"]+)"\)"#)).unwrap();
     }
 
     ATTACK.captures(input)
 }
 
-pub fn kill(input: &str) -> Option<regex::Captures> {
+pub fn kill(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref KILL: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] killed "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)"(?: \(([^)]+)\))?"#).unwrap();
+        static ref KILL: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] killed "#,
+            player!(),
+            r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] with "([^"]+)"(?: \(([^)]+)\))?"#
+        ))
+        .unwrap();
     }
 
     KILL.captures(input)
 }
 
-pub fn assist(input: &str) -> Option<regex::Captures> {
+pub fn assist(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref ASSIST: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" assisted killing "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>""#).unwrap();
+        static ref ASSIST: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" assisted killing "#,
+            player!()
+        ))
+        .unwrap();
     }
 
     ASSIST.captures(input)
 }
 
-pub fn suicide(input: &str) -> Option<regex::Captures> {
+pub fn suicide(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref SUICIDE: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" \[-?\d{1,5} -?\d{1,5} -?\d{1,5}\] committed suicide with "[a-zA-Z0-9]{1,20}""#).unwrap();
+        static ref SUICIDE: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] committed suicide with "([^"]+)""#
+        ))
+        .unwrap();
     }
 
     SUICIDE.captures(input)
 }
 
-pub fn blinded(input: &str) -> Option<regex::Captures> {
+pub fn blinded(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref BLINDED: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" blinded for (\d\.\d{2}) by "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" from flashbang entindex [0-9\s]{1,5}"#).unwrap();
+        static ref BLINDED: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" blinded for (\d+\.\d+) by "#,
+            player!(),
+            r#" from flashbang entindex (\d+)"#
+        ))
+        .unwrap();
     }
 
     BLINDED.captures(input)
 }
 
-pub fn bomb(input: &str) -> Option<regex::Captures> {
+pub fn round_start(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref ROUND_START: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): World triggered "Round_Start""#
+        )
+        .unwrap();
+    }
+
+    ROUND_START.captures(input)
+}
+
+pub fn round_end(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref ROUND_END: regex::Regex = regex::Regex::new(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): World triggered "Round_End""#
+        )
+        .unwrap();
+    }
+
+    ROUND_END.captures(input)
+}
+
+/// A player doing something with the bomb. Planting (and, in newer logs, beginning to plant and defusing) also says which bombsite it happened at
+pub fn bomb(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref BOMB: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" triggered "(Got_The_Bomb|Dropped_The_Bomb|Planted_The_Bomb|Begin_Bomb_Defuse_With(?:out)?_Kit|Defused_The_Bomb)""#).unwrap();
+        static ref BOMB: regex::Regex = regex::Regex::new(concat!(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#, player!(), r#" triggered "(Got_The_Bomb|Spawned_With_The_Bomb|Dropped_The_Bomb|Bomb_Begin_Plant|Planted_The_Bomb|Begin_Bomb_Defuse_With(?:out)?_Kit|Defused_The_Bomb)"(?: at bombsite ([A-Z]))?"#)).unwrap();
     }
 
     BOMB.captures(input)
 }
 
-pub fn hostage(input: &str) -> Option<regex::Captures> {
+/// A team winning a round, along with the reason (e.g. `Target_Bombed`, `Bomb_Defused`, `CTs_Win`) and the score afterwards.
+/// The bomb exploding is only recorded by the `Target_Bombed` reason.
+pub fn round_won(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref ROUND_WON: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): Team "(CT|TERRORIST)" triggered "SFUI_Notice_([A-Za-z_]+)" \(CT "(\d+)"\) \(T "(\d+)"\)"#).unwrap();
+    }
+
+    ROUND_WON.captures(input)
+}
+
+/// A player picking up or dropping off a hostage, with their position. Dropping off happens both on reaching the rescue zone and on being killed while carrying one
+pub fn hostage_carry(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref HOSTAGE_CARRY: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\] (picked up|dropped off) a hostage"#
+        ))
+        .unwrap();
+    }
+
+    HOSTAGE_CARRY.captures(input)
+}
+
+pub fn hostage(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref HOSTAGE: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" triggered "(Touched_A_Hostage|Rescued_A_Hostage|Killed_A_Hostage)""#).unwrap();
+        static ref HOSTAGE: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" triggered "(Touched_A_Hostage|Rescued_A_Hostage|Killed_A_Hostage)""#
+        ))
+        .unwrap();
     }
 
     HOSTAGE.captures(input)
 }
 
-pub fn chicken(input: &str) -> Option<regex::Captures> {
+pub fn purchased(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref PURCHASED: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" purchased "([^"]+)""#
+        ))
+        .unwrap();
+    }
+
+    PURCHASED.captures(input)
+}
+
+/// A player leaving the buyzone, with everything they're carrying, e.g. `[ weapon_knife weapon_hkp2000 kevlar(100) helmet ]`
+pub fn left_buyzone(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref LEFT_BUYZONE: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" left buyzone with \[([^\[\]]*)\]"#
+        ))
+        .unwrap();
+    }
+
+    LEFT_BUYZONE.captures(input)
+}
+
+/// A player's money changing, which is only logged with `mp_logmoney 1`. Captures the money before, the change, the money after and what was bought, if anything
+pub fn money_change(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref MONEY_CHANGE: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" money change (\d+)([+-]\d+) = \$(\d+)(?: \(tracked\))?(?: \(purchase: ([^)]+)\))?"#
+        ))
+        .unwrap();
+    }
+
+    MONEY_CHANGE.captures(input)
+}
+
+pub fn picked_up(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref PICKED_UP: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" picked up "([^"]+)""#
+        ))
+        .unwrap();
+    }
+
+    PICKED_UP.captures(input)
+}
+
+/// A player throwing a grenade, with their position. Flashbangs also have an entindex, which matches the one in `blinded` lines
+pub fn threw(input: &str) -> Option<regex::Captures<'_>> {
+    lazy_static! {
+        static ref THREW: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" threw ([a-z]+) \[(-?\d{1,5} -?\d{1,5} -?\d{1,5})\](?: flashbang entindex (\d+)\))?"#
+        ))
+        .unwrap();
+    }
+
+    THREW.captures(input)
+}
+
+pub fn chicken(input: &str) -> Option<regex::Captures<'_>> {
     lazy_static! {
-        static ref CHICKEN: regex::Regex = regex::Regex::new(r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "([^<]+)<\d{1,3}><(STEAM[^>]+)><(CT|TERRORIST)>" .+? killed other "chicken.+? with "([^"]+)""#).unwrap();
+        static ref CHICKEN: regex::Regex = regex::Regex::new(concat!(
+            r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "#,
+            player!(),
+            r#" .+? killed other "chicken.+? with "([^"]+)""#
+        ))
+        .unwrap();
     }
 
     CHICKEN.captures(input)
 }
+
+/// A user-defined pattern from the config, compiled and ready to match lines against
+#[derive(Debug)]
+pub struct CustomPattern {
+    name: String,
+    regex: regex::Regex,
+    timestamp: Option<usize>,
+    fields: HashMap<String, usize>,
+}
+
+impl CustomPattern {
+    /// Compile the pattern, checking that the capture groups it refers to by number exist
+    pub fn compile(pattern: &config::CustomPattern) -> Result<CustomPattern, String> {
+        let regex = regex::Regex::new(&pattern.pattern).map_err(|why| why.to_string())?;
+        let fields = pattern.fields.clone().unwrap_or_default();
+
+        // Group 0 is the whole match, so there are only `captures_len() - 1` groups to pick from
+        let groups = regex.captures_len() - 1;
+        if let Some(group) = pattern.timestamp.filter(|group| *group > groups) {
+            return Err(format!(
+                "the timestamp is capture group {}, but there are only {}",
+                group, groups
+            ));
+        }
+        if let Some((field, group)) = fields.iter().find(|(_, group)| **group > groups) {
+            return Err(format!(
+                "field '{}' is capture group {}, but there are only {}",
+                field, group, groups
+            ));
+        }
+
+        Ok(CustomPattern {
+            name: pattern.name.clone(),
+            regex,
+            timestamp: pattern.timestamp,
+            fields,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// If the line matches, return its timestamp (if the pattern captures it) and its fields
+    pub fn captures<'a>(
+        &self,
+        input: &'a str,
+    ) -> Option<(Option<&'a str>, HashMap<String, String>)> {
+        let captures = self.regex.captures(input)?;
+
+        let timestamp = self
+            .timestamp
+            .and_then(|group| captures.get(group))
+            .map(|timestamp| timestamp.as_str());
+
+        let mut fields = HashMap::new();
+
+        for name in self.regex.capture_names().flatten() {
+            if let Some(value) = captures.name(name) {
+                fields.insert(name.to_owned(), value.as_str().to_owned());
+            }
+        }
+
+        for (field, group) in self.fields.iter() {
+            if let Some(value) = captures.get(*group) {
+                fields.insert(field.to_owned(), value.as_str().to_owned());
+            }
+        }
+
+        Some((timestamp, fields))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_tuple() {
+        assert_eq!(
+            player("Alice<8><STEAM_1:1:00000001><CT>"),
+            PlayerTuple {
+                name: "Alice",
+                userid: "8",
+                steam_id: "STEAM_1:1:00000001",
+                team: Some("CT"),
+            }
+        );
+
+        // No team
+        assert_eq!(
+            player("Sam<5><STEAM_1:0:00000003>"),
+            PlayerTuple {
+                name: "Sam",
+                userid: "5",
+                steam_id: "STEAM_1:0:00000003",
+                team: None,
+            }
+        );
+
+        // Empty team, four digit userid, and a bot
+        assert_eq!(
+            player("Brett<1234><BOT><>"),
+            PlayerTuple {
+                name: "Brett",
+                userid: "1234",
+                steam_id: "BOT",
+                team: Some(""),
+            }
+        );
+    }
+
+    #[test]
+    fn test_player_tuple_nasty_names() {
+        let nasty_names = [
+            "<3 sniper",
+            "<<>>",
+            "a<1><STEAM_1:0:1>",
+            "\"quoted\"",
+            "name with spaces > you",
+            ">",
+            "<",
+        ];
+
+        for name in nasty_names.iter() {
+            let tuple = format!("{}<1234><STEAM_1:0:00000007><TERRORIST>", name);
+            let parsed = player(&tuple);
+            assert_eq!(parsed.name, *name);
+            assert_eq!(parsed.userid, "1234");
+            assert_eq!(parsed.steam_id, "STEAM_1:0:00000007");
+            assert_eq!(parsed.team, Some("TERRORIST"));
+        }
+    }
+
+    #[test]
+    fn test_nasty_names_in_lines() {
+        let line = r#"L 05/04/2020 - 21:50:03: "<3 sniper<1234><STEAM_1:1:00000004><TERRORIST>" [-1156 -582 512] attacked "Bob "the <builder>"<6><BOT><CT>" [-1119 -1044 430] with "deagle" (damage "51") (damage_armor "0") (health "49") (armor "0") (hitgroup "chest")"#;
+        let captures = attack(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "<3 sniper");
+        assert_eq!(&captures[3], "-1156 -582 512");
+        assert_eq!(player(&captures[4]).name, r##"Bob "the <builder>""##);
+        assert_eq!(player(&captures[4]).steam_id, "BOT");
+        assert_eq!(&captures[6], "deagle");
+
+        // The victim's name looks like the end of a tuple and another kill, but the killer's tuple still ends at the killer's name
+        let line = r#"L 05/04/2020 - 21:52:43: "Dean<9><STEAM_1:1:00000004><TERRORIST>" [-413 -726 320] killed "x<2><STEAM_1:0:1><CT>" [0 0 0] killed "James<3><STEAM_1:0:00000005><CT>" [-58 -1253 320] with "sg556""#;
+        let captures = kill(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Dean");
+        assert_eq!(player(&captures[2]).userid, "9");
+        assert_eq!(&captures[3], "-413 -726 320");
+        assert_eq!(
+            player(&captures[4]).name,
+            r#"x<2><STEAM_1:0:1><CT>" [0 0 0] killed "James"#
+        );
+        assert_eq!(player(&captures[4]).steam_id, "STEAM_1:0:00000005");
+        assert_eq!(&captures[5], "-58 -1253 320");
+
+        let line = r#"L 05/04/2020 - 21:49:07: "<3 sniper<5><STEAM_1:0:00000003>" switched from team <Unassigned> to <TERRORIST>"#;
+        let captures = switched_team(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "<3 sniper");
+        assert_eq!(&captures[3], "Unassigned");
+        assert_eq!(&captures[4], "TERRORIST");
+
+        // The opening quote is missing, so this shouldn't match anything
+        let line = r#"L 05/04/2020 - 21:49:14: James<3><STEAM_1:0:00000005><>" switched from team <Unassigned> to <TERRORIST>"#;
+        assert!(switched_team(line).is_none());
+    }
+
+    #[test]
+    fn test_bomb() {
+        let line = r#"L 05/04/2020 - 21:53:41: "Sam<5><STEAM_1:0:00000003><TERRORIST>" triggered "Planted_The_Bomb" at bombsite B"#;
+        let captures = bomb(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Sam");
+        assert_eq!(&captures[3], "Planted_The_Bomb");
+        assert_eq!(&captures[4], "B");
+
+        // Older logs don't say which bombsite
+        let line = r#"L 05/04/2020 - 21:53:41: "Sam<5><STEAM_1:0:00000003><TERRORIST>" triggered "Planted_The_Bomb""#;
+        let captures = bomb(line).unwrap();
+        assert!(captures.get(4).is_none());
+
+        let line = r#"L 05/04/2020 - 21:53:50: "Alice<8><STEAM_1:1:00000001><CT>" triggered "Begin_Bomb_Defuse_Without_Kit""#;
+        let captures = bomb(line).unwrap();
+        assert_eq!(&captures[3], "Begin_Bomb_Defuse_Without_Kit");
+
+        let line = r#"L 05/04/2020 - 21:54:21: Team "TERRORIST" triggered "SFUI_Notice_Target_Bombed" (CT "3") (T "5")"#;
+        let captures = round_won(line).unwrap();
+        assert_eq!(&captures[2], "TERRORIST");
+        assert_eq!(&captures[3], "Target_Bombed");
+        assert_eq!(&captures[4], "3");
+        assert_eq!(&captures[5], "5");
+    }
+
+    #[test]
+    fn test_hostage_carry() {
+        let line = r#"L 05/04/2020 - 21:50:42: "Alice<8><STEAM_1:1:00000001><CT>" [-1276 1155 512] picked up a hostage"#;
+        let captures = hostage_carry(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Alice");
+        assert_eq!(&captures[3], "-1276 1155 512");
+        assert_eq!(&captures[4], "picked up");
+
+        let line = r#"L 05/04/2020 - 21:51:48: "Alice<8><STEAM_1:1:00000001><CT>" [-101 -1183 284] dropped off a hostage"#;
+        let captures = hostage_carry(line).unwrap();
+        assert_eq!(&captures[4], "dropped off");
+    }
+
+    #[test]
+    fn test_suicide_and_blinded() {
+        let line = r#"L 05/04/2020 - 21:57:14: "James<3><STEAM_1:0:00000005><TERRORIST>" [-320 -2014 273] committed suicide with "hegrenade""#;
+        let captures = suicide(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "James");
+        assert_eq!(&captures[3], "-320 -2014 273");
+        assert_eq!(&captures[4], "hegrenade");
+
+        // Note the trailing space
+        let line = r#"L 05/04/2020 - 21:55:01: "James<3><STEAM_1:0:00000005><TERRORIST>" blinded for 3.68 by "Bob<6><STEAM_1:0:00000002><CT>" from flashbang entindex 478 "#;
+        let captures = blinded(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "James");
+        assert_eq!(&captures[3], "3.68");
+        assert_eq!(player(&captures[4]).name, "Bob");
+        assert_eq!(&captures[5], "478");
+    }
+
+    #[test]
+    fn test_custom_pattern() {
+        let mut fields = HashMap::new();
+        fields.insert("player".to_owned(), 2);
+
+        let pattern = CustomPattern::compile(&config::CustomPattern {
+            name: "clutch_won".to_owned(),
+            pattern: r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "(.+)<\d+><(?P<steam_id>[^<>]+)><[^<>]*>" triggered "clutch_won""#.to_owned(),
+            timestamp: Some(1),
+            fields: Some(fields),
+        })
+        .unwrap();
+
+        let line =
+            r#"L 05/04/2020 - 21:57:14: "Mark<7><STEAM_1:1:00000006><CT>" triggered "clutch_won""#;
+        let (timestamp, fields) = pattern.captures(line).unwrap();
+        assert_eq!(timestamp, Some("05/04/2020 - 21:57:14"));
+        assert_eq!(fields["player"], "Mark");
+        assert_eq!(fields["steam_id"], "STEAM_1:1:00000006");
+
+        assert!(pattern
+            .captures(r#"L 05/04/2020 - 21:57:14: World triggered "Round_End""#)
+            .is_none());
+    }
+
+    #[test]
+    fn test_custom_pattern_groups() {
+        let custom_pattern = |timestamp, field| {
+            let mut fields = HashMap::new();
+            fields.insert("player".to_owned(), field);
+
+            config::CustomPattern {
+                name: "clutch_won".to_owned(),
+                pattern: r#"^L (\d{2}/\d{2}/\d{4} - \d{2}:\d{2}:\d{2}): "(.+)<\d+>"#.to_owned(),
+                timestamp,
+                fields: Some(fields),
+            }
+        };
+
+        assert!(CustomPattern::compile(&custom_pattern(Some(2), 2)).is_ok());
+        assert!(CustomPattern::compile(&custom_pattern(None, 1)).is_ok());
+
+        // There's no group 3
+        assert_eq!(
+            CustomPattern::compile(&custom_pattern(Some(3), 2))
+                .err()
+                .unwrap(),
+            "the timestamp is capture group 3, but there are only 2"
+        );
+        assert!(CustomPattern::compile(&custom_pattern(Some(1), 3)).is_err());
+    }
+
+    #[test]
+    fn test_match_status() {
+        let line = r#"L 05/04/2020 - 21:49:30: MatchStatus: Team playing "CT": Team "Awesome" <3"#;
+        let captures = match_status_team(line).unwrap();
+        assert_eq!(&captures[2], "CT");
+        assert_eq!(&captures[3], r#"Team "Awesome" <3"#);
+
+        let line = r#"L 05/04/2020 - 21:57:14: MatchStatus: Score: 1:4 on map "cs_agency" RoundsPlayed: 5"#;
+        let captures = match_status_score(line).unwrap();
+        assert_eq!(&captures[2], "1");
+        assert_eq!(&captures[3], "4");
+        assert_eq!(&captures[4], "cs_agency");
+        assert_eq!(&captures[5], "5");
+    }
+
+    #[test]
+    fn test_game_over() {
+        let line = r#"L 05/04/2020 - 22:02:47: Game Over: competitive <allmaps> cs_agency score 6:3 after 14 min"#;
+        let captures = game_over(line).unwrap();
+        assert_eq!(&captures[2], "competitive");
+        assert_eq!(&captures[3], "<allmaps>");
+        assert_eq!(&captures[4], "cs_agency");
+        assert_eq!(&captures[5], "6");
+        assert_eq!(&captures[6], "3");
+        assert_eq!(&captures[7], "14");
+    }
+
+    #[test]
+    fn test_changed_name() {
+        let line = r#"L 05/04/2020 - 21:53:10: "Alice<8><STEAM_1:1:00000001><CT>" changed name to "Alice "the <great>"""#;
+        let captures = changed_name(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Alice");
+        assert_eq!(&captures[3], r#"Alice "the <great>""#);
+
+        let line = r#"L 05/04/2020 - 21:49:06: "Bob<6><STEAM_1:0:00000002><Unassigned>" changed name to "Robert""#;
+        let captures = changed_name(line).unwrap();
+        assert_eq!(player(&captures[2]).team, Some("Unassigned"));
+        assert_eq!(&captures[3], "Robert");
+    }
+
+    #[test]
+    fn test_threw() {
+        let line = r#"L 05/04/2020 - 21:53:12: "Dean<9><STEAM_1:1:00000004><TERRORIST>" threw hegrenade [-1315 -482 350]"#;
+        let captures = threw(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Dean");
+        assert_eq!(&captures[3], "hegrenade");
+        assert_eq!(&captures[4], "-1315 -482 350");
+        assert!(captures.get(5).is_none());
+
+        // Note the unbalanced parenthesis
+        let line = r#"L 05/04/2020 - 21:55:01: "Bob<6><STEAM_1:0:00000002><CT>" threw flashbang [-1311 -377 329] flashbang entindex 478)"#;
+        let captures = threw(line).unwrap();
+        assert_eq!(&captures[3], "flashbang");
+        assert_eq!(&captures[5], "478");
+    }
+
+    #[test]
+    fn test_economy() {
+        let line = r#"L 05/04/2020 - 21:49:33: "Alice<8><STEAM_1:1:00000001><CT>" purchased "item_kevlar""#;
+        let captures = purchased(line).unwrap();
+        assert_eq!(player(&captures[2]).name, "Alice");
+        assert_eq!(&captures[3], "item_kevlar");
+
+        let line = r#"L 05/04/2020 - 21:49:52: "Alice<8><STEAM_1:1:00000001><CT>" left buyzone with [ weapon_knife weapon_hkp2000 kevlar(100) helmet ]"#;
+        let captures = left_buyzone(line).unwrap();
+        let items: Vec<&str> = captures[3].split_whitespace().collect();
+        assert_eq!(
+            items,
+            ["weapon_knife", "weapon_hkp2000", "kevlar(100)", "helmet"]
+        );
+
+        let line = r#"L 05/04/2020 - 21:49:33: "Alice<8><STEAM_1:1:00000001><CT>" money change 800-650 = $150 (tracked) (purchase: item_kevlar)"#;
+        let captures = money_change(line).unwrap();
+        assert_eq!(&captures[3], "800");
+        assert_eq!(&captures[4], "-650");
+        assert_eq!(&captures[5], "150");
+        assert_eq!(&captures[6], "item_kevlar");
+
+        let line = r#"L 05/04/2020 - 21:50:01: "Alice<8><STEAM_1:1:00000001><CT>" money change 150+300 = $450 (tracked)"#;
+        let captures = money_change(line).unwrap();
+        assert_eq!(&captures[4], "+300");
+        assert!(captures.get(6).is_none());
+
+        let line =
+            r#"L 05/04/2020 - 21:50:12: "Alice<8><STEAM_1:1:00000001><CT>" picked up "ak47""#;
+        let captures = picked_up(line).unwrap();
+        assert_eq!(&captures[3], "ak47");
+
+        // Not to be confused with carrying a hostage
+        let line = r#"L 05/04/2020 - 21:50:42: "Alice<8><STEAM_1:1:00000001><CT>" [-1276 1155 512] picked up a hostage"#;
+        assert!(picked_up(line).is_none());
+    }
+
+    #[test]
+    fn test_server_lines() {
+        let line = r#"L 05/04/2020 - 21:48:01: server_cvar: "mp_maxrounds" "16""#;
+        let captures = server_cvar(line).unwrap();
+        assert_eq!(&captures[1], "05/04/2020 - 21:48:01");
+        assert_eq!(&captures[2], "mp_maxrounds");
+        assert_eq!(&captures[3], "16");
+
+        // Cvars can be set to nothing
+        let line = r#"L 05/04/2020 - 21:48:01: server_cvar: "mp_teamname_1" """#;
+        let captures = server_cvar(line).unwrap();
+        assert_eq!(&captures[3], "");
+
+        let line = r#"L 05/04/2020 - 21:48:02: rcon from "192.168.1.10:52614": command "mp_restartgame 1""#;
+        let captures = rcon(line).unwrap();
+        assert_eq!(&captures[2], "192.168.1.10:52614");
+        assert_eq!(&captures[3], "mp_restartgame 1");
+
+        let line = r#"L 05/04/2020 - 21:47:55: Loading map "de_dust2""#;
+        let captures = loading_map(line).unwrap();
+        assert_eq!(&captures[2], "de_dust2");
+
+        let line = r#"L 05/04/2020 - 21:47:58: Started map "de_dust2" (CRC "-1286410427")"#;
+        let captures = started_map(line).unwrap();
+        assert_eq!(&captures[2], "de_dust2");
+        assert_eq!(&captures[3], "-1286410427");
+
+        let line = r#"L 05/04/2020 - 21:47:58: Started map "de_dust2""#;
+        let captures = started_map(line).unwrap();
+        assert_eq!(&captures[2], "de_dust2");
+        assert!(captures.get(3).is_none());
+
+        assert!(server_cvar(
+            r#"L 05/04/2020 - 21:48:02: rcon from "192.168.1.10:52614": command "status""#
+        )
+        .is_none());
+    }
+}
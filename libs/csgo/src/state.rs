@@ -2,6 +2,7 @@ use std::collections::HashMap;
 
 use serde::{Deserialize, Serialize};
 
+use super::economy;
 use super::geo;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -11,6 +12,12 @@ pub struct Map {
     scale: u8,
 }
 
+impl Default for Map {
+    fn default() -> Map {
+        Map::new()
+    }
+}
+
 impl Map {
     pub fn from_name(name: &str) -> Map {
         Map {
@@ -31,17 +38,97 @@ impl Map {
     pub fn name(&self) -> &str {
         &self.name
     }
+
+    /// Hostage rescue maps are prefixed with `cs_`, bomb defusal maps with `de_`
+    pub fn is_hostage_map(&self) -> bool {
+        self.name.starts_with("cs_")
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+/// A player in a team. Players are the same player if they have the same ID, even if they've changed their name
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     name: String,
     id: String,
+    bot: bool,
+}
+
+impl PartialEq for Player {
+    fn eq(&self, other: &Player) -> bool {
+        self.id == other.id
+    }
 }
 
 impl Player {
-    pub fn new(name: String, id: String) -> Player {
-        Player { name, id }
+    pub fn new(name: String, id: String, bot: bool) -> Player {
+        Player { name, id, bot }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn bot(&self) -> bool {
+        self.bot
+    }
+}
+
+/// A name a player went by, and when they were first seen using it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NameUse {
+    name: String,
+    epoch: i64,
+}
+
+/// Everything we know about who a player is, keyed by their ID, which is their (possibly translated) Steam ID, or a synthetic ID for bots
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlayerIdentity {
+    id: String,
+    steam_id: String,
+    userid: String,
+    names: Vec<NameUse>,
+}
+
+impl PlayerIdentity {
+    pub fn new(id: &str, steam_id: &str, userid: &str, name: &str, epoch: i64) -> PlayerIdentity {
+        PlayerIdentity {
+            id: id.to_owned(),
+            steam_id: steam_id.to_owned(),
+            userid: userid.to_owned(),
+            names: vec![NameUse {
+                name: name.to_owned(),
+                epoch,
+            }],
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn steam_id(&self) -> &str {
+        &self.steam_id
+    }
+
+    /// The userid the server gave the player, which changes if they reconnect
+    pub fn userid(&self) -> &str {
+        &self.userid
+    }
+
+    /// The name the player is currently using
+    pub fn name(&self) -> &str {
+        &self.names.last().unwrap().name
+    }
+
+    /// Every name the player has used, oldest first
+    pub fn names(&self) -> impl Iterator<Item = (&str, i64)> {
+        self.names
+            .iter()
+            .map(|name| (name.name.as_str(), name.epoch))
     }
 }
 
@@ -58,9 +145,165 @@ impl Team {
             players: Vec::new(),
         }
     }
+
+    pub fn players(&self) -> &Vec<Player> {
+        &self.players
+    }
+}
+
+/// What a player has right now, as far as we can tell from the logs.
+/// The money is an estimate worked out from purchases, kills and round results, unless the server logs money changes (`mp_logmoney 1`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState {
+    health: u32,
+    armor: u32,
+    helmet: bool,
+    alive: bool,
+    weapons: Vec<String>,
+    money: i64,
+}
+
+impl PlayerState {
+    pub fn new(side: &str, money: i64) -> PlayerState {
+        PlayerState {
+            health: 100,
+            armor: 0,
+            helmet: false,
+            alive: true,
+            weapons: economy::default_weapons(side),
+            money,
+        }
+    }
+
+    pub fn health(&self) -> u32 {
+        self.health
+    }
+
+    pub fn armor(&self) -> u32 {
+        self.armor
+    }
+
+    pub fn helmet(&self) -> bool {
+        self.helmet
+    }
+
+    pub fn alive(&self) -> bool {
+        self.alive
+    }
+
+    pub fn weapons(&self) -> &Vec<String> {
+        &self.weapons
+    }
+
+    pub fn money(&self) -> i64 {
+        self.money
+    }
+
+    /// Add an item the player has bought or picked up
+    fn add_item(&mut self, item: &str) {
+        match economy::item_name(item) {
+            "kevlar" | "vest" => self.armor = 100,
+            "assaultsuit" | "vesthelm" => {
+                self.armor = 100;
+                self.helmet = true;
+            }
+            item => self.weapons.push(item.to_owned()),
+        }
+    }
+
+    /// Spawn at the start of a round. Having survived the last round, the player keeps their weapons and armor
+    fn spawn(&mut self, side: &str) {
+        if !self.alive {
+            self.armor = 0;
+            self.helmet = false;
+            self.weapons = economy::default_weapons(side);
+        }
+
+        self.health = 100;
+        self.alive = true;
+    }
+
+    fn die(&mut self) {
+        self.health = 0;
+        self.alive = false;
+        self.weapons.clear();
+    }
 }
 
+/// A team that keeps its identity when the sides are swapped at halftime, named "Team A" (which starts on CT) and "Team B", unless the `mp_teamname_1` and `mp_teamname_2` cvars say otherwise
 #[derive(Debug, Serialize, Deserialize)]
+pub struct LogicalTeam {
+    name: String,
+    /// The name the server gave the team in its MatchStatus lines, if it did
+    clan_name: Option<String>,
+    side: String,
+    players: Vec<String>,
+    score: u32,
+    half_scores: Vec<u32>,
+    rounds_won_as_ct: u32,
+    rounds_won_as_t: u32,
+    /// The losses the loss bonus is paid for. As in CS:GO, a win only knocks one off, rather than wiping out the lot
+    loss_streak: u32,
+}
+
+impl LogicalTeam {
+    pub fn new(name: &str, side: &str) -> LogicalTeam {
+        LogicalTeam {
+            name: name.to_owned(),
+            clan_name: None,
+            side: side.to_owned(),
+            players: Vec::new(),
+            score: 0,
+            half_scores: vec![0],
+            rounds_won_as_ct: 0,
+            rounds_won_as_t: 0,
+            loss_streak: 0,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn clan_name(&self) -> Option<&str> {
+        self.clan_name.as_deref()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    /// How many rounds the team won in each half, including any overtime halves
+    pub fn half_scores(&self) -> &Vec<u32> {
+        &self.half_scores
+    }
+
+    fn win_round(&mut self, half: usize) {
+        self.score += 1;
+        self.loss_streak = self.loss_streak.saturating_sub(1);
+
+        if self.half_scores.len() <= half {
+            self.half_scores.resize(half + 1, 0);
+        }
+        self.half_scores[half] += 1;
+
+        if self.side == "CT" {
+            self.rounds_won_as_ct += 1;
+        } else {
+            self.rounds_won_as_t += 1;
+        }
+    }
+
+    fn swap_side(&mut self) {
+        self.side = if self.side == "CT" {
+            "TERRORIST".to_owned()
+        } else {
+            "CT".to_owned()
+        };
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
     epoch: i64,
     detail: HashMap<String, String>,
@@ -72,92 +315,3119 @@ impl Event {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GameState {
-    map: Map,
-    mode: String,
-    cts: Team,
-    ts: Team,
-    events: Vec<Event>,
+/// The game mode, as named in the Game Over line, or as set by the `game_type` and `game_mode` cvars
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    Casual,
+    Competitive,
+    /// Named "scrimcomp2v2" in the logs
+    Wingman,
+    Deathmatch,
+    /// Named "gungameprogressive" in the logs
+    ArmsRace,
+    /// Named "gungametrbomb" in the logs
+    Demolition,
+    Other(String),
 }
 
-impl GameState {
-    pub fn new() -> GameState {
-        GameState {
-            map: Map::new(),
-            mode: String::new(),
-            cts: Team::new("CT"),
-            ts: Team::new("TERRORIST"),
-            events: Vec::new(),
+impl GameMode {
+    pub fn from_name(name: &str) -> GameMode {
+        match name {
+            "casual" => GameMode::Casual,
+            "competitive" => GameMode::Competitive,
+            "scrimcomp2v2" => GameMode::Wingman,
+            "deathmatch" => GameMode::Deathmatch,
+            "gungameprogressive" => GameMode::ArmsRace,
+            "gungametrbomb" => GameMode::Demolition,
+            _ => GameMode::Other(name.to_owned()),
         }
     }
 
-    pub fn map(&self) -> &Map {
-        &self.map
+    /// Work out the mode from the `game_type` and `game_mode` cvars, which is how the server is told which to play
+    pub fn from_cvars(game_type: &str, game_mode: &str) -> GameMode {
+        match (game_type, game_mode) {
+            ("0", "0") => GameMode::Casual,
+            ("0", "1") => GameMode::Competitive,
+            ("0", "2") => GameMode::Wingman,
+            ("1", "0") => GameMode::ArmsRace,
+            ("1", "1") => GameMode::Demolition,
+            ("1", "2") => GameMode::Deathmatch,
+            _ => GameMode::Other(format!("{}/{}", game_type, game_mode)),
+        }
     }
 
-    pub fn map_mut(&mut self) -> &mut Map {
-        &mut self.map
+    /// The name used in the logs
+    pub fn name(&self) -> &str {
+        match self {
+            GameMode::Casual => "casual",
+            GameMode::Competitive => "competitive",
+            GameMode::Wingman => "scrimcomp2v2",
+            GameMode::Deathmatch => "deathmatch",
+            GameMode::ArmsRace => "gungameprogressive",
+            GameMode::Demolition => "gungametrbomb",
+            GameMode::Other(name) => name,
+        }
     }
 
-    pub fn mode(&self) -> &String {
-        &self.mode
+    /// Deathmatch and arms race are free-for-alls, so there's no winning team
+    pub fn has_winning_team(&self) -> bool {
+        !matches!(self, GameMode::Deathmatch | GameMode::ArmsRace)
     }
+}
+
+/// The group of maps the server rotates through, as named in the Game Over line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MapGroup {
+    /// Written as `<allmaps>`, when the server doesn't have a map group set
+    AllMaps,
+    Active,
+    Reserves,
+    Hostage,
+    Other(String),
+}
 
-    pub fn mode_mut(&mut self) -> &mut String {
-        &mut self.mode
+impl MapGroup {
+    pub fn from_name(name: &str) -> MapGroup {
+        match name {
+            "<allmaps>" | "" => MapGroup::AllMaps,
+            "mg_active" => MapGroup::Active,
+            "mg_reserves" => MapGroup::Reserves,
+            "mg_hostage" => MapGroup::Hostage,
+            _ => MapGroup::Other(name.to_owned()),
+        }
     }
 
-    pub fn cts(&self) -> &Team {
-        &self.cts
+    /// The name used in the logs
+    pub fn name(&self) -> &str {
+        match self {
+            MapGroup::AllMaps => "<allmaps>",
+            MapGroup::Active => "mg_active",
+            MapGroup::Reserves => "mg_reserves",
+            MapGroup::Hostage => "mg_hostage",
+            MapGroup::Other(name) => name,
+        }
     }
+}
 
-    pub fn cts_mut(&mut self) -> &mut Team {
-        &mut self.cts
+/// The damage one player did to another, and in how many hits
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Damage {
+    hits: u32,
+    damage: u32,
+}
+
+impl Damage {
+    pub fn hits(&self) -> u32 {
+        self.hits
     }
 
-    pub fn ts(&self) -> &Team {
-        &self.ts
+    pub fn damage(&self) -> u32 {
+        self.damage
     }
+}
 
-    pub fn ts_mut(&mut self) -> &mut Team {
-        &mut self.ts
+/// The damage each player did to each other player, keyed by attacker ID and then victim ID
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DamageTable {
+    given: HashMap<String, HashMap<String, Damage>>,
+}
+
+impl DamageTable {
+    /// The damage the attacker did to the victim, if they hit them at all
+    pub fn between(&self, attacker_id: &str, victim_id: &str) -> Option<&Damage> {
+        self.given.get(attacker_id)?.get(victim_id)
     }
 
-    pub fn events(&self) -> &Vec<Event> {
-        &self.events
+    /// The damage the player gave, by victim
+    pub fn given_by(&self, player_id: &str) -> Vec<(&str, &Damage)> {
+        self.given
+            .get(player_id)
+            .map(|given| {
+                given
+                    .iter()
+                    .map(|(victim_id, damage)| (victim_id.as_str(), damage))
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
-    pub fn events_mut(&mut self) -> &mut Vec<Event> {
-        &mut self.events
+    /// The damage the player took, by attacker
+    pub fn taken_by(&self, player_id: &str) -> Vec<(&str, &Damage)> {
+        self.given
+            .iter()
+            .filter_map(|(attacker_id, given)| {
+                given
+                    .get(player_id)
+                    .map(|damage| (attacker_id.as_str(), damage))
+            })
+            .collect()
     }
 
-    pub fn add_player_to_team(&mut self, new_player: Player, team_name: &str) {
-        // First, remove the player if they're already in a team
-        if self.cts().players.contains(&new_player) {
-            self.cts_mut()
-                .players
-                .retain(|player| player != &new_player); // i.e. retain all the current players that are NOT equal to new_player
-        } else if self.ts().players.contains(&new_player) {
-            self.ts_mut().players.retain(|player| player != &new_player); // i.e. retain all the current players that are NOT equal to new_player
+    fn add(&mut self, attacker_id: &str, victim_id: &str, damage: u32) {
+        let entry = self
+            .given
+            .entry(attacker_id.to_owned())
+            .or_default()
+            .entry(victim_id.to_owned())
+            .or_default();
+
+        entry.hits += 1;
+        entry.damage += damage;
+    }
+}
+
+/// Who damaged whom, like the damage summary CS:GO prints to the console. Friendly fire is kept apart from damage to enemies
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DamageMatrix {
+    enemy: DamageTable,
+    friendly: DamageTable,
+}
+
+impl DamageMatrix {
+    pub fn enemy(&self) -> &DamageTable {
+        &self.enemy
+    }
+
+    pub fn friendly(&self) -> &DamageTable {
+        &self.friendly
+    }
+
+    fn add(&mut self, attacker_id: &str, victim_id: &str, damage: u32, friendly: bool) {
+        if friendly {
+            self.friendly.add(attacker_id, victim_id, damage);
+        } else {
+            self.enemy.add(attacker_id, victim_id, damage);
         }
+    }
+}
 
-        // Second, add the new player to their team
-        match team_name {
-            "CT" => {
-                log::info!("Adding player {:?} to team {}", &new_player, &team_name);
-                self.cts_mut().players.push(new_player);
-            }
-            "TERRORIST" => {
-                log::info!("Adding player {:?} to team {}", &new_player, &team_name);
-                self.ts_mut().players.push(new_player);
-            }
-            _ => {
-                log::warn!("Unknown team: {}", team_name)
+/// A kill within a round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kill {
+    epoch: i64,
+    killer: String,
+    killer_position: String,
+    victim: String,
+    victim_position: String,
+    weapon: String,
+    headshot: bool,
+    team_kill: bool,
+    /// The teammate who killed the killer soon after, if anyone did
+    traded_by: Option<String>,
+}
+
+impl Kill {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        epoch: i64,
+        killer: String,
+        killer_position: String,
+        victim: String,
+        victim_position: String,
+        weapon: String,
+        headshot: bool,
+        team_kill: bool,
+    ) -> Kill {
+        Kill {
+            epoch,
+            killer,
+            killer_position,
+            victim,
+            victim_position,
+            weapon,
+            headshot,
+            team_kill,
+            traded_by: None,
+        }
+    }
+
+    pub fn killer(&self) -> &str {
+        &self.killer
+    }
+
+    pub fn victim(&self) -> &str {
+        &self.victim
+    }
+
+    pub fn weapon(&self) -> &str {
+        &self.weapon
+    }
+
+    pub fn headshot(&self) -> bool {
+        self.headshot
+    }
+
+    pub fn traded_by(&self) -> Option<&str> {
+        self.traded_by.as_deref()
+    }
+
+    /// Killing yourself or a teammate doesn't count as a kill
+    fn counts(&self) -> bool {
+        self.killer != self.victim && !self.team_kill
+    }
+}
+
+/// A kill that avenged a teammate, killing their killer soon after they died
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Trade {
+    player: String,
+    victim: String,
+    traded: String,
+    seconds: i64,
+    distance: String,
+}
+
+impl Trade {
+    /// The player who made the trade
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+
+    /// The killer they killed
+    pub fn victim(&self) -> &str {
+        &self.victim
+    }
+
+    /// The teammate whose death was traded
+    pub fn traded(&self) -> &str {
+        &self.traded
+    }
+
+    /// The seconds between the teammate's death and the trade
+    pub fn seconds(&self) -> i64 {
+        self.seconds
+    }
+
+    /// The distance between where the teammate died and where their killer died
+    pub fn distance(&self) -> &str {
+        &self.distance
+    }
+}
+
+/// A player left alone against one or more enemies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Clutch {
+    player: String,
+    side: String,
+    opponents: usize,
+    epoch: i64,
+    won: Option<bool>,
+}
+
+impl Clutch {
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+
+    pub fn side(&self) -> &str {
+        &self.side
+    }
+
+    /// How many enemies were alive when the clutch began, the X in 1vX
+    pub fn opponents(&self) -> usize {
+        self.opponents
+    }
+
+    /// Whether the player's side won the round, once it's over
+    pub fn won(&self) -> Option<bool> {
+        self.won
+    }
+}
+
+/// The name for a number of kills in a round, from a 2k up to an ace
+pub fn multi_kill_name(kills: usize) -> &'static str {
+    match kills {
+        0 | 1 => "",
+        2 => "2k",
+        3 => "3k",
+        4 => "4k",
+        _ => "ace",
+    }
+}
+
+/// How a player did in a round, for the scoreboard
+struct RoundScore {
+    player_id: String,
+    kills: usize,
+    survived: bool,
+    kast: bool,
+}
+
+/// Who was still alive on each side at a point in a round
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Alive {
+    epoch: i64,
+    ct: Vec<String>,
+    terrorist: Vec<String>,
+}
+
+/// Everything that happened in a round, from Round_Start to Round_End
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Round {
+    number: u32,
+    start_epoch: i64,
+    end_epoch: Option<i64>,
+    winner: Option<String>,
+    winner_team: Option<String>,
+    reason: Option<String>,
+    ct_score: Option<u32>,
+    terrorist_score: Option<u32>,
+    alive: Vec<Alive>,
+    kills: Vec<Kill>,
+    assists: Vec<String>,
+    damage: DamageMatrix,
+    clutch: Option<Clutch>,
+    mvp: Option<String>,
+    /// Where the round's events are in the match's list of events, which they're only kept in once
+    events: std::ops::Range<usize>,
+}
+
+impl Round {
+    pub fn new(number: u32, start_epoch: i64, ct: Vec<String>, terrorist: Vec<String>) -> Round {
+        Round {
+            number,
+            start_epoch,
+            end_epoch: None,
+            winner: None,
+            winner_team: None,
+            reason: None,
+            ct_score: None,
+            terrorist_score: None,
+            alive: vec![Alive {
+                epoch: start_epoch,
+                ct,
+                terrorist,
+            }],
+            kills: Vec::new(),
+            assists: Vec::new(),
+            damage: DamageMatrix::default(),
+            clutch: None,
+            mvp: None,
+            events: 0..0,
+        }
+    }
+
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn start_epoch(&self) -> i64 {
+        self.start_epoch
+    }
+
+    pub fn winner(&self) -> Option<&str> {
+        self.winner.as_deref()
+    }
+
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    pub fn in_progress(&self) -> bool {
+        self.end_epoch.is_none()
+    }
+
+    /// Record which side won the round, the logical team playing on it, why, and the score by side afterwards
+    pub fn set_result(
+        &mut self,
+        winner: &str,
+        winner_team: Option<String>,
+        reason: &str,
+        ct_score: u32,
+        terrorist_score: u32,
+    ) {
+        self.winner = Some(winner.to_owned());
+        self.winner_team = winner_team;
+        self.reason = Some(reason.to_owned());
+        self.ct_score = Some(ct_score);
+        self.terrorist_score = Some(terrorist_score);
+
+        if let Some(clutch) = self.clutch.as_mut() {
+            clutch.won = Some(clutch.side == winner);
+        }
+    }
+
+    /// The players still alive on each side, CT first
+    pub fn alive(&self) -> (&Vec<String>, &Vec<String>) {
+        let alive = self.alive.last().unwrap();
+        (&alive.ct, &alive.terrorist)
+    }
+
+    /// Take the player out of the alive players, if they were one of them.
+    /// Returns the clutch that began if that left a teammate alone against the enemy, which can only happen once a round
+    fn player_died(&mut self, player_id: &str, epoch: i64) -> Option<&Clutch> {
+        let (ct, terrorist) = self.alive();
+
+        if ct.iter().chain(terrorist.iter()).any(|id| id == player_id) {
+            let alive = Alive {
+                epoch,
+                ct: ct.iter().filter(|id| *id != player_id).cloned().collect(),
+                terrorist: terrorist
+                    .iter()
+                    .filter(|id| *id != player_id)
+                    .cloned()
+                    .collect(),
+            };
+            self.alive.push(alive);
+
+            if self.clutch.is_none() {
+                let side = self.side_of(player_id)?;
+                let (ct, terrorist) = self.alive();
+                let (teammates, enemies) = if side == "CT" {
+                    (ct, terrorist)
+                } else {
+                    (terrorist, ct)
+                };
+
+                if teammates.len() == 1 && !enemies.is_empty() {
+                    self.clutch = Some(Clutch {
+                        player: teammates[0].clone(),
+                        side: side.to_owned(),
+                        opponents: enemies.len(),
+                        epoch,
+                        won: None,
+                    });
+
+                    return self.clutch.as_ref();
+                }
             }
         }
 
-        log::info!("CT: {:?}", self.cts());
-        log::info!("TERRORIST: {:?}", self.ts());
+        None
+    }
+
+    pub fn kills(&self) -> &Vec<Kill> {
+        &self.kills
+    }
+
+    /// Who damaged whom in the round
+    pub fn damage(&self) -> &DamageMatrix {
+        &self.damage
+    }
+
+    pub fn clutch(&self) -> Option<&Clutch> {
+        self.clutch.as_ref()
+    }
+
+    /// The players who killed two or more enemies in the round, and how many
+    pub fn multi_kills(&self) -> Vec<(String, usize)> {
+        self.scores()
+            .into_iter()
+            .filter(|score| score.kills >= 2)
+            .map(|score| (score.player_id, score.kills))
+            .collect()
+    }
+
+    /// The player who was the round's MVP, as far as we can tell
+    pub fn mvp(&self) -> Option<&str> {
+        self.mvp.as_deref()
+    }
+
+    /// The side the player started the round on
+    fn side_of(&self, player_id: &str) -> Option<&'static str> {
+        let start = self.alive.first().unwrap();
+
+        if start.ct.iter().any(|id| id == player_id) {
+            Some("CT")
+        } else if start.terrorist.iter().any(|id| id == player_id) {
+            Some("TERRORIST")
+        } else {
+            None
+        }
+    }
+
+    /// The first kill of the round, if there's been one
+    pub fn opening_kill(&self) -> Option<&Kill> {
+        self.kills.iter().find(|kill| kill.counts())
+    }
+
+    /// The player's death was traded if a teammate killed their killer soon after
+    fn traded(&self, player_id: &str) -> bool {
+        self.kills
+            .iter()
+            .any(|kill| kill.victim == player_id && kill.traded_by.is_some())
+    }
+
+    /// Add a kill to the round, returning the trade it made if it killed someone who had just killed a teammate of the killer
+    fn add_kill(&mut self, kill: Kill, trade_window: i64) -> Option<Trade> {
+        let side = self.side_of(&kill.killer);
+        let death = self.kills.iter().position(|death| {
+            kill.counts()
+                && death.counts()
+                && death.killer == kill.victim
+                && death.traded_by.is_none()
+                && self.side_of(&death.victim) == side
+                && kill.epoch - death.epoch <= trade_window
+        });
+
+        let trade = death.map(|index| {
+            let death = &mut self.kills[index];
+            death.traded_by = Some(kill.killer.clone());
+
+            Trade {
+                player: kill.killer.clone(),
+                victim: kill.victim.clone(),
+                traded: death.victim.clone(),
+                seconds: kill.epoch - death.epoch,
+                distance: geo::metres_between_points(&death.victim_position, &kill.victim_position),
+            }
+        });
+
+        self.kills.push(kill);
+
+        trade
+    }
+
+    /// How each player who started the round did in it
+    fn scores(&self) -> Vec<RoundScore> {
+        let start = self.alive.first().unwrap();
+        let (ct, terrorist) = self.alive();
+
+        start
+            .ct
+            .iter()
+            .chain(start.terrorist.iter())
+            .map(|player_id| {
+                let kills = self
+                    .kills
+                    .iter()
+                    .filter(|kill| &kill.killer == player_id && kill.counts())
+                    .count();
+                let assisted = self.assists.iter().any(|id| id == player_id);
+                let survived = ct.iter().chain(terrorist.iter()).any(|id| id == player_id);
+
+                RoundScore {
+                    player_id: player_id.clone(),
+                    kills,
+                    survived,
+                    kast: kills > 0 || assisted || survived || self.traded(player_id),
+                }
+            })
+            .collect()
+    }
+
+    /// The MVP is whoever planted the bomb if it exploded, or defused it, and otherwise the player on the winning side with the most kills
+    fn choose_mvp(&mut self, bomb_player: Option<String>) {
+        let winner = match self.winner.clone() {
+            Some(winner) => winner,
+            None => return,
+        };
+
+        self.mvp = bomb_player.or_else(|| {
+            let mut scores: Vec<RoundScore> = self
+                .scores()
+                .into_iter()
+                .filter(|score| score.kills > 0)
+                .filter(|score| self.side_of(&score.player_id) == Some(winner.as_str()))
+                .collect();
+
+            // A tie goes to whoever comes first in the round's list of players
+            scores.sort_by_key(|score| std::cmp::Reverse(score.kills));
+            scores.first().map(|score| score.player_id.clone())
+        });
+    }
+}
+
+/// The server rules in force, as set by `server_cvar` lines. These persist across match resets, as they're a property of the server rather than of any one match
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Rules {
+    max_rounds: Option<u32>,
+    overtime: Option<bool>,
+    overtime_max_rounds: Option<u32>,
+    friendly_fire: Option<bool>,
+    cvars: HashMap<String, String>,
+}
+
+impl Rules {
+    pub fn new() -> Rules {
+        Rules::default()
+    }
+
+    pub fn cvar(&self, name: &str) -> Option<&str> {
+        self.cvars.get(name).map(|value| value.as_str())
+    }
+
+    /// Record the new value of a cvar, updating any of the rules it corresponds to
+    pub fn set_cvar(&mut self, name: &str, value: &str) {
+        match name {
+            "mp_maxrounds" => self.max_rounds = value.parse::<u32>().ok(),
+            "mp_overtime_enable" => self.overtime = Some(value != "0"),
+            "mp_overtime_maxrounds" => self.overtime_max_rounds = value.parse::<u32>().ok(),
+            "mp_friendlyfire" => self.friendly_fire = Some(value != "0"),
+            _ => {}
+        }
+
+        self.cvars.insert(name.to_owned(), value.to_owned());
+    }
+}
+
+/// How a bomb plant turned out
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BombOutcome {
+    Defused,
+    Exploded,
+    /// The round ended some other way first, e.g. the CTs were all killed, or time ran out during the freeze time of the next round
+    RoundEnded,
+}
+
+/// A bomb plant, and how long it was until it was defused, exploded, or the round ended
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BombPlant {
+    round: u32,
+    site: Option<String>,
+    planter: String,
+    defuser: Option<String>,
+    planted_epoch: i64,
+    outcome: Option<BombOutcome>,
+    outcome_epoch: Option<i64>,
+    seconds_to_outcome: Option<i64>,
+}
+
+impl BombPlant {
+    pub fn new(round: u32, site: Option<String>, planter: String, planted_epoch: i64) -> BombPlant {
+        BombPlant {
+            round,
+            site,
+            planter,
+            defuser: None,
+            planted_epoch,
+            outcome: None,
+            outcome_epoch: None,
+            seconds_to_outcome: None,
+        }
+    }
+
+    pub fn site(&self) -> Option<&str> {
+        self.site.as_deref()
+    }
+
+    pub fn set_defuser(&mut self, defuser: String) {
+        self.defuser = Some(defuser);
+    }
+
+    /// Record how the plant turned out, returning the number of seconds from the plant to the outcome
+    pub fn resolve(&mut self, outcome: BombOutcome, epoch: i64) -> i64 {
+        let seconds = epoch - self.planted_epoch;
+
+        self.outcome = Some(outcome);
+        self.outcome_epoch = Some(epoch);
+        self.seconds_to_outcome = Some(seconds);
+
+        seconds
+    }
+}
+
+/// A player's hits and kills with one weapon
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WeaponStats {
+    hits: u32,
+    damage: u32,
+    kills: u32,
+    headshot_kills: u32,
+    /// What the average time to kill is worked out from
+    #[serde(skip)]
+    timed_kills: u32,
+    #[serde(skip)]
+    time_to_kill_total: i64,
+    time_to_kill: f64,
+}
+
+impl WeaponStats {
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn kills(&self) -> u32 {
+        self.kills
+    }
+
+    pub fn headshot_kills(&self) -> u32 {
+        self.headshot_kills
+    }
+
+    /// The average seconds from the first hit on a victim to killing them
+    pub fn time_to_kill(&self) -> f64 {
+        self.time_to_kill
+    }
+
+    fn add_time_to_kill(&mut self, seconds: i64) {
+        self.timed_kills += 1;
+        self.time_to_kill_total += seconds;
+        self.time_to_kill =
+            round_to_hundredths(self.time_to_kill_total as f64 / f64::from(self.timed_kills));
+    }
+}
+
+/// The part of the body hit, with the left and right arms and legs lumped together
+pub fn hitgroup_name(hitgroup: &str) -> &str {
+    match hitgroup {
+        "left arm" | "right arm" => "arms",
+        "left leg" | "right leg" => "legs",
+        hitgroup => hitgroup,
+    }
+}
+
+/// How many seconds a teammate has to kill the killer for a death to count as traded, unless the config says otherwise
+pub const TRADE_WINDOW_SECONDS: i64 = 5;
+
+/// A player's line on the scoreboard. The kills and deaths are all that decides the ranking in deathmatch and arms race.
+/// The averages (ADR, HS%, KAST and rating) are worked out again as each round ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerScore {
+    player: Player,
+    kills: u32,
+    deaths: u32,
+    assists: u32,
+    damage: u32,
+    headshot_kills: u32,
+    mvps: u32,
+    rounds_played: u32,
+    kast_rounds: u32,
+    /// The number of rounds with 1, 2, 3, 4 and 5 or more kills
+    multi_kills: [u32; 5],
+    clutches: u32,
+    clutches_won: u32,
+    weapons: HashMap<String, WeaponStats>,
+    hitgroups: HashMap<String, u32>,
+    /// Every weapon's kills and times to kill lumped together
+    #[serde(skip)]
+    all_weapons: WeaponStats,
+    time_to_kill: f64,
+    utility_damage: u32,
+    grenades_thrown: HashMap<String, u32>,
+    unused_utility_at_death: u32,
+    unused_utility_at_round_end: u32,
+    enemies_flashed: u32,
+    enemy_blind_time: f64,
+    team_flashes: u32,
+    team_blind_time: f64,
+    flash_assists: u32,
+    opening_kills: u32,
+    opening_deaths: u32,
+    trade_kills: u32,
+    traded_deaths: u32,
+    adr: f64,
+    headshot_percentage: f64,
+    kast: f64,
+    rating: f64,
+    entry_success: f64,
+}
+
+impl PlayerScore {
+    pub fn new(player: Player) -> PlayerScore {
+        PlayerScore {
+            player,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+            damage: 0,
+            headshot_kills: 0,
+            mvps: 0,
+            rounds_played: 0,
+            kast_rounds: 0,
+            multi_kills: [0; 5],
+            clutches: 0,
+            clutches_won: 0,
+            weapons: HashMap::new(),
+            hitgroups: HashMap::new(),
+            all_weapons: WeaponStats::default(),
+            time_to_kill: 0.0,
+            utility_damage: 0,
+            grenades_thrown: HashMap::new(),
+            unused_utility_at_death: 0,
+            unused_utility_at_round_end: 0,
+            enemies_flashed: 0,
+            enemy_blind_time: 0.0,
+            team_flashes: 0,
+            team_blind_time: 0.0,
+            flash_assists: 0,
+            opening_kills: 0,
+            opening_deaths: 0,
+            trade_kills: 0,
+            traded_deaths: 0,
+            adr: 0.0,
+            headshot_percentage: 0.0,
+            kast: 0.0,
+            rating: 0.0,
+            entry_success: 0.0,
+        }
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn kills(&self) -> u32 {
+        self.kills
+    }
+
+    pub fn deaths(&self) -> u32 {
+        self.deaths
+    }
+
+    /// What free-for-all games are ranked by: most kills first, with fewer deaths breaking ties.
+    /// Players with the same key are level, so if they're top of the ranking it's a draw
+    pub fn ranking_key(&self) -> (std::cmp::Reverse<u32>, u32) {
+        (std::cmp::Reverse(self.kills), self.deaths)
+    }
+
+    pub fn assists(&self) -> u32 {
+        self.assists
+    }
+
+    pub fn damage(&self) -> u32 {
+        self.damage
+    }
+
+    pub fn mvps(&self) -> u32 {
+        self.mvps
+    }
+
+    pub fn rounds_played(&self) -> u32 {
+        self.rounds_played
+    }
+
+    /// The number of rounds in which the player got exactly `kills` kills, counting five or more as an ace
+    pub fn multi_kills(&self, kills: usize) -> u32 {
+        match kills {
+            0 => 0,
+            kills => self.multi_kills[kills.min(5) - 1],
+        }
+    }
+
+    /// The number of 1vX clutches the player was in, and how many of them they won
+    pub fn clutches(&self) -> (u32, u32) {
+        (self.clutches, self.clutches_won)
+    }
+
+    /// The player's hits, damage and kills, by weapon
+    pub fn weapons(&self) -> &HashMap<String, WeaponStats> {
+        &self.weapons
+    }
+
+    /// The number of hits on enemies, by the part of the body hit, e.g. "head" or "legs"
+    pub fn hitgroups(&self) -> &HashMap<String, u32> {
+        &self.hitgroups
+    }
+
+    /// The average seconds from the first hit on a victim to killing them
+    pub fn time_to_kill(&self) -> f64 {
+        self.time_to_kill
+    }
+
+    /// The damage the player did to enemies with HE grenades and fire
+    pub fn utility_damage(&self) -> u32 {
+        self.utility_damage
+    }
+
+    /// The number of grenades the player threw, by type, e.g. "flashbang"
+    pub fn grenades_thrown(&self) -> &HashMap<String, u32> {
+        &self.grenades_thrown
+    }
+
+    /// The number of grenades the player was still carrying when they died, and when rounds ended with them alive
+    pub fn unused_utility(&self) -> (u32, u32) {
+        (
+            self.unused_utility_at_death,
+            self.unused_utility_at_round_end,
+        )
+    }
+
+    /// The number of enemies the player flashed, and the seconds they were blind for in total
+    pub fn enemies_flashed(&self) -> (u32, f64) {
+        (self.enemies_flashed, self.enemy_blind_time)
+    }
+
+    /// The number of times the player flashed a teammate or themselves, and the seconds they were blind for in total
+    pub fn team_flashes(&self) -> (u32, f64) {
+        (self.team_flashes, self.team_blind_time)
+    }
+
+    /// The number of kills on enemies the player had flashed, made by a teammate while the enemy was still blind
+    pub fn flash_assists(&self) -> u32 {
+        self.flash_assists
+    }
+
+    /// The number of opening duels, i.e. first kills of a round, the player won and lost
+    pub fn opening_duels(&self) -> (u32, u32) {
+        (self.opening_kills, self.opening_deaths)
+    }
+
+    /// The percentage of opening duels the player won
+    pub fn entry_success(&self) -> f64 {
+        self.entry_success
+    }
+
+    /// The number of teammates' deaths the player traded
+    pub fn trade_kills(&self) -> u32 {
+        self.trade_kills
+    }
+
+    /// The number of the player's deaths that a teammate traded
+    pub fn traded_deaths(&self) -> u32 {
+        self.traded_deaths
+    }
+
+    /// Average damage per round
+    pub fn adr(&self) -> f64 {
+        self.adr
+    }
+
+    /// The percentage of kills that were headshots
+    pub fn headshot_percentage(&self) -> f64 {
+        self.headshot_percentage
+    }
+
+    /// The percentage of rounds in which the player got a kill or an assist, survived or was traded
+    pub fn kast(&self) -> f64 {
+        self.kast
+    }
+
+    /// The HLTV 1.0 rating, where 1.0 is average
+    pub fn rating(&self) -> f64 {
+        self.rating
+    }
+
+    /// Work the averages out again
+    fn update_averages(&mut self) {
+        let percentage = |count: u32, total: u32| {
+            if total == 0 {
+                0.0
+            } else {
+                round_to_hundredths(100.0 * f64::from(count) / f64::from(total))
+            }
+        };
+
+        self.headshot_percentage = percentage(self.headshot_kills, self.kills);
+        self.entry_success =
+            percentage(self.opening_kills, self.opening_kills + self.opening_deaths);
+        self.kast = percentage(self.kast_rounds, self.rounds_played);
+
+        if self.rounds_played == 0 {
+            return;
+        }
+
+        let rounds = f64::from(self.rounds_played);
+        self.adr = round_to_hundredths(f64::from(self.damage) / rounds);
+
+        let kill_rating = f64::from(self.kills) / rounds / 0.679;
+        let survival_rating =
+            f64::from(self.rounds_played.saturating_sub(self.deaths)) / rounds / 0.317;
+        let multi_kill_rating = self
+            .multi_kills
+            .iter()
+            .enumerate()
+            .map(|(index, count)| ((index + 1) * (index + 1)) as f64 * f64::from(*count))
+            .sum::<f64>()
+            / rounds
+            / 1.277;
+
+        self.rating =
+            round_to_hundredths((kill_rating + 0.7 * survival_rating + multi_kill_rating) / 2.7);
+    }
+}
+
+fn round_to_hundredths(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// How a match turned out
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchResult {
+    /// A team game, won by the named logical team, or drawn if there's no winner
+    Team { winner: Option<String> },
+    /// A free-for-all game, with the IDs of the players ranked from first to last
+    Ranking { players: Vec<String> },
+}
+
+/// What a player killed themselves with, broadly speaking
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuicideCause {
+    /// Fall damage, which the logs attribute to "world"
+    Fall,
+    /// Map hazards, such as drowning or falling out of the map
+    World,
+    Grenade,
+    Other,
+}
+
+impl SuicideCause {
+    pub fn from_weapon(weapon: &str) -> SuicideCause {
+        match weapon {
+            "world" => SuicideCause::Fall,
+            "worldspawn" | "trigger_hurt" => SuicideCause::World,
+            "hegrenade" | "inferno" | "molotov" | "incgrenade" => SuicideCause::Grenade,
+            _ => SuicideCause::Other,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            SuicideCause::Fall => "fall",
+            SuicideCause::World => "world",
+            SuicideCause::Grenade => "grenade",
+            SuicideCause::Other => "other",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Suicide {
+    round: u32,
+    epoch: i64,
+    player: String,
+    position: String,
+    weapon: String,
+    cause: SuicideCause,
+}
+
+impl Suicide {
+    pub fn new(
+        round: u32,
+        epoch: i64,
+        player: String,
+        position: String,
+        weapon: String,
+    ) -> Suicide {
+        let cause = SuicideCause::from_weapon(&weapon);
+
+        Suicide {
+            round,
+            epoch,
+            player,
+            position,
+            weapon,
+            cause,
+        }
+    }
+
+    pub fn cause(&self) -> SuicideCause {
+        self.cause
+    }
+}
+
+/// A player being blinded by a flashbang. A team flash is one thrown by a player on the same team, including the player themselves
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Blind {
+    round: u32,
+    epoch: i64,
+    attacker: String,
+    victim: String,
+    duration: f64,
+    team_flash: bool,
+    entindex: u32,
+}
+
+impl Blind {
+    pub fn new(
+        round: u32,
+        epoch: i64,
+        attacker: String,
+        victim: String,
+        duration: f64,
+        team_flash: bool,
+        entindex: u32,
+    ) -> Blind {
+        Blind {
+            round,
+            epoch,
+            attacker,
+            victim,
+            duration,
+            team_flash,
+            entindex,
+        }
+    }
+}
+
+/// What happened to the hostages during a round
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HostageStatus {
+    round: u32,
+    carriers: Vec<String>,
+    pickups: u32,
+    rescued_by: Vec<String>,
+    killed_by: Vec<String>,
+}
+
+impl HostageStatus {
+    pub fn new(round: u32) -> HostageStatus {
+        HostageStatus {
+            round,
+            carriers: Vec::new(),
+            pickups: 0,
+            rescued_by: Vec::new(),
+            killed_by: Vec::new(),
+        }
+    }
+
+    pub fn pick_up(&mut self, player_id: String) {
+        self.pickups += 1;
+        self.carriers.push(player_id);
+    }
+
+    pub fn drop_off(&mut self, player_id: &str) {
+        self.carriers.retain(|carrier| carrier != player_id);
+    }
+
+    pub fn rescue(&mut self, player_id: String) {
+        self.drop_off(&player_id);
+        self.rescued_by.push(player_id);
+    }
+
+    pub fn kill(&mut self, player_id: String) {
+        self.killed_by.push(player_id);
+    }
+
+    /// The IDs of the players who rescued a hostage, once for each hostage they rescued
+    pub fn rescued_by(&self) -> &Vec<String> {
+        &self.rescued_by
+    }
+
+    pub fn killed_by(&self) -> &Vec<String> {
+        &self.killed_by
+    }
+}
+
+/// Where a match is up to. Events that happen during the warmup are still dispatched, but are marked as such so they can be left out of the stats
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchPhase {
+    Warmup,
+    Live,
+    /// Between the last round of a half and the first round of the next, while the teams swap sides
+    Halftime,
+    Overtime,
+    Ended,
+}
+
+impl MatchPhase {
+    pub fn name(&self) -> &str {
+        match self {
+            MatchPhase::Warmup => "warmup",
+            MatchPhase::Live => "live",
+            MatchPhase::Halftime => "halftime",
+            MatchPhase::Overtime => "overtime",
+            MatchPhase::Ended => "ended",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GameState {
+    map: Map,
+    mode: Option<GameMode>,
+    map_group: Option<MapGroup>,
+    rules: Rules,
+    phase: MatchPhase,
+    players: HashMap<String, PlayerIdentity>,
+    live: HashMap<String, PlayerState>,
+    /// When each attacker first hit each victim in the current round
+    #[serde(skip)]
+    first_hits: HashMap<(String, String), i64>,
+    trade_window: i64,
+    cts: Team,
+    ts: Team,
+    teams: Vec<LogicalTeam>,
+    half: usize,
+    round_number: u32,
+    rounds: Vec<Round>,
+    between_rounds: bool,
+    side_swaps: usize,
+    scores: Vec<PlayerScore>,
+    result: Option<MatchResult>,
+    bomb_plants: Vec<BombPlant>,
+    hostages: Vec<HostageStatus>,
+    suicides: Vec<Suicide>,
+    blinds: Vec<Blind>,
+    damage: DamageMatrix,
+    events: Vec<Event>,
+}
+
+impl Default for GameState {
+    fn default() -> GameState {
+        GameState::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> GameState {
+        GameState {
+            map: Map::new(),
+            mode: None,
+            map_group: None,
+            rules: Rules::new(),
+            phase: MatchPhase::Warmup,
+            players: HashMap::new(),
+            live: HashMap::new(),
+            first_hits: HashMap::new(),
+            trade_window: TRADE_WINDOW_SECONDS,
+            cts: Team::new("CT"),
+            ts: Team::new("TERRORIST"),
+            teams: vec![
+                LogicalTeam::new("Team A", "CT"),
+                LogicalTeam::new("Team B", "TERRORIST"),
+            ],
+            half: 0,
+            round_number: 0,
+            rounds: Vec::new(),
+            between_rounds: false,
+            side_swaps: 0,
+            scores: Vec::new(),
+            result: None,
+            bomb_plants: Vec::new(),
+            hostages: Vec::new(),
+            suicides: Vec::new(),
+            blinds: Vec::new(),
+            damage: DamageMatrix::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Start afresh, for a new match or a restart of the current one. The server rules, the map, the mode, the players and who's on which team all carry over, but everything that happened is forgotten
+    pub fn reset(&mut self) {
+        let map = std::mem::take(&mut self.map);
+        let rules = std::mem::take(&mut self.rules);
+        let players = std::mem::take(&mut self.players);
+        let cts = std::mem::replace(&mut self.cts, Team::new("CT"));
+        let ts = std::mem::replace(&mut self.ts, Team::new("TERRORIST"));
+
+        // The teams keep their names, but start again on their original sides
+        let teams = self
+            .teams
+            .iter()
+            .zip(["CT", "TERRORIST"].iter())
+            .map(|(team, side)| LogicalTeam {
+                clan_name: team.clan_name.clone(),
+                ..LogicalTeam::new(team.name(), side)
+            })
+            .collect();
+
+        // The mode and map group are set up on the server, so they carry over too
+        let mode = self.mode.take();
+        let map_group = self.map_group.take();
+
+        *self = GameState {
+            map,
+            mode,
+            map_group,
+            rules,
+            players,
+            trade_window: self.trade_window,
+            phase: self.phase,
+            cts,
+            ts,
+            teams,
+            ..GameState::new()
+        };
+
+        self.update_team_players();
+    }
+
+    pub fn map(&self) -> &Map {
+        &self.map
+    }
+
+    pub fn map_mut(&mut self) -> &mut Map {
+        &mut self.map
+    }
+
+    pub fn mode(&self) -> Option<&GameMode> {
+        self.mode.as_ref()
+    }
+
+    pub fn set_mode(&mut self, mode: GameMode) {
+        self.mode = Some(mode);
+    }
+
+    pub fn set_map_group(&mut self, map_group: MapGroup) {
+        self.map_group = Some(map_group);
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    pub fn rules_mut(&mut self) -> &mut Rules {
+        &mut self.rules
+    }
+
+    pub fn phase(&self) -> MatchPhase {
+        self.phase
+    }
+
+    /// Move to the given phase, returning the previous one if it's a change
+    pub fn set_phase(&mut self, phase: MatchPhase) -> Option<MatchPhase> {
+        if self.phase == phase {
+            None
+        } else {
+            Some(std::mem::replace(&mut self.phase, phase))
+        }
+    }
+
+    pub fn cts(&self) -> &Team {
+        &self.cts
+    }
+
+    pub fn cts_mut(&mut self) -> &mut Team {
+        &mut self.cts
+    }
+
+    pub fn ts(&self) -> &Team {
+        &self.ts
+    }
+
+    pub fn ts_mut(&mut self) -> &mut Team {
+        &mut self.ts
+    }
+
+    /// Rename one of the logical teams, where 1 is the team that started on CT and 2 is the team that started on TERRORIST
+    pub fn rename_team(&mut self, number: usize, name: &str) {
+        if let Some(team) = self.teams.get_mut(number.wrapping_sub(1)) {
+            team.name = name.to_owned();
+        }
+    }
+
+    /// Record the clan name of the logical team playing on the given side, as given by the server's MatchStatus lines, which it's then known by
+    pub fn set_clan_name(&mut self, side: &str, clan_name: &str) {
+        if let Some(team) = self.teams.iter_mut().find(|team| team.side == side) {
+            team.clan_name = Some(clan_name.to_owned());
+            team.name = clan_name.to_owned();
+        }
+    }
+
+    /// The logical team currently playing on the given side
+    pub fn team_on_side(&self, side: &str) -> Option<&LogicalTeam> {
+        self.teams.iter().find(|team| team.side == side)
+    }
+
+    /// Credit the round to the logical team playing on the given side, returning that team. Both teams are paid for the round, going by why it was won
+    pub fn win_round(&mut self, side: &str, reason: &str) -> Option<&LogicalTeam> {
+        let half = self.half;
+        let max_money = self.max_money();
+
+        for team in self.teams.iter_mut() {
+            let won = team.side == side;
+
+            if won {
+                team.win_round(half);
+            } else {
+                team.loss_streak = (team.loss_streak + 1).min(economy::MAX_LOSS_STREAK);
+            }
+
+            let reward = economy::round_reward(won, reason, team.loss_streak);
+            let players = if team.side == "CT" {
+                &self.cts
+            } else {
+                &self.ts
+            };
+
+            for player in players.players.iter() {
+                if let Some(state) = self.live.get_mut(&player.id) {
+                    state.money = (state.money + reward).min(max_money);
+                }
+            }
+        }
+
+        self.team_on_side(side)
+    }
+
+    /// How many seconds a teammate has to kill the killer for a death to count as traded
+    pub fn set_trade_window(&mut self, seconds: i64) {
+        self.trade_window = seconds;
+    }
+
+    /// Count a kill for the killer and a death for the victim, and pay the killer for it. Killing yourself or a teammate doesn't count as a kill.
+    /// Returns the trade, if the kill avenged a teammate, and whether it was the opening kill of the round
+    pub fn record_kill(
+        &mut self,
+        killer: Player,
+        victim: Player,
+        kill: Kill,
+    ) -> (Option<Trade>, bool) {
+        let team_kill = kill.team_kill;
+        let headshot = kill.headshot;
+        let weapon = kill.weapon.clone();
+        let epoch = kill.epoch;
+        let reward = if team_kill {
+            economy::TEAM_KILL_PENALTY
+        } else {
+            economy::kill_reward(&kill.weapon)
+        };
+
+        let flash_assister = if kill.counts() {
+            self.flash_assister(&kill)
+        } else {
+            None
+        };
+        if let Some(assister) = flash_assister.and_then(|id| self.player_on_team(&id)) {
+            self.player_score_mut(assister).flash_assists += 1;
+        }
+
+        let trade_window = self.trade_window;
+        let (trade, opening) = match self.current_round_mut().filter(|round| round.in_progress()) {
+            Some(round) => {
+                let trade = round.add_kill(kill, trade_window);
+                let opening = round.kills.last().is_some_and(Kill::counts)
+                    && round.kills.iter().filter(|kill| kill.counts()).count() == 1;
+                (trade, opening)
+            }
+            None => (None, false),
+        };
+
+        if let Some(trade) = &trade {
+            if let Some(traded) = self.player_on_team(&trade.traded) {
+                self.player_score_mut(traded).traded_deaths += 1;
+            }
+            self.player_score_mut(killer.clone()).trade_kills += 1;
+        }
+
+        if opening {
+            self.player_score_mut(killer.clone()).opening_kills += 1;
+            self.player_score_mut(victim.clone()).opening_deaths += 1;
+        }
+
+        if killer != victim {
+            let max_money = self.max_money();
+            if let Some(state) = self.live.get_mut(&killer.id) {
+                state.money = (state.money + reward).clamp(0, max_money);
+            }
+        }
+
+        if killer != victim && !team_kill {
+            let first_hit = self
+                .first_hits
+                .remove(&(killer.id.clone(), victim.id.clone()));
+
+            let score = self.player_score_mut(killer);
+            score.kills += 1;
+            if headshot {
+                score.headshot_kills += 1;
+            }
+
+            let stats = score.weapons.entry(weapon).or_default();
+            stats.kills += 1;
+            if headshot {
+                stats.headshot_kills += 1;
+            }
+            if let Some(first_hit) = first_hit {
+                stats.add_time_to_kill(epoch - first_hit);
+                score.all_weapons.add_time_to_kill(epoch - first_hit);
+                score.time_to_kill = score.all_weapons.time_to_kill;
+            }
+
+            score.update_averages();
+        }
+
+        self.record_death(victim);
+
+        (trade, opening)
+    }
+
+    /// Count a death against the player, whether they were killed or killed themselves
+    fn record_death(&mut self, player: Player) {
+        let score = self.player_score_mut(player);
+        score.deaths += 1;
+        score.update_averages();
+    }
+
+    pub fn record_assist(&mut self, assister: Player) {
+        if let Some(round) = self.current_round_mut().filter(|round| round.in_progress()) {
+            round.assists.push(assister.id.clone());
+        }
+
+        self.player_score_mut(assister).assists += 1;
+    }
+
+    /// Who damaged whom over the whole match
+    pub fn damage(&self) -> &DamageMatrix {
+        &self.damage
+    }
+
+    /// Add a hit by one player on another to the damage matrices, for the round and the match
+    pub fn record_damage(
+        &mut self,
+        attacker_id: &str,
+        victim_id: &str,
+        damage: u32,
+        friendly: bool,
+    ) {
+        if let Some(round) = self.current_round_mut().filter(|round| round.in_progress()) {
+            round.damage.add(attacker_id, victim_id, damage, friendly);
+        }
+
+        self.damage.add(attacker_id, victim_id, damage, friendly);
+    }
+
+    /// Add a hit on an enemy, and the damage dealt, to the attacker's score
+    pub fn record_hit(
+        &mut self,
+        attacker: Player,
+        victim_id: &str,
+        weapon: &str,
+        hitgroup: &str,
+        damage: u32,
+        epoch: i64,
+    ) {
+        // The clock for the time to kill starts at the first hit
+        self.first_hits
+            .entry((attacker.id.clone(), victim_id.to_owned()))
+            .or_insert(epoch);
+
+        let score = self.player_score_mut(attacker);
+        let stats = score.weapons.entry(weapon.to_owned()).or_default();
+        stats.hits += 1;
+        stats.damage += damage;
+
+        *score
+            .hitgroups
+            .entry(hitgroup_name(hitgroup).to_owned())
+            .or_default() += 1;
+
+        score.damage += damage;
+        if economy::is_utility_damage(weapon) {
+            score.utility_damage += damage;
+        }
+    }
+
+    /// The player threw a grenade, so they no longer have it
+    pub fn throw_grenade(&mut self, player: Player, grenade: &str) {
+        let weapons = &mut self.live_player_mut(&player.id).weapons;
+
+        // Incendiaries are thrown as molotovs
+        let names: &[&str] = match grenade {
+            "molotov" | "incgrenade" => &["molotov", "incgrenade"],
+            grenade => &[grenade],
+        };
+        if let Some(index) = weapons
+            .iter()
+            .position(|weapon| names.contains(&weapon.as_str()))
+        {
+            weapons.remove(index);
+        }
+
+        *self
+            .player_score_mut(player)
+            .grenades_thrown
+            .entry(grenade.to_owned())
+            .or_default() += 1;
+    }
+
+    /// The number of grenades the player is carrying
+    fn grenades_held(&self, player_id: &str) -> u32 {
+        self.live
+            .get(player_id)
+            .map(|state| {
+                state
+                    .weapons
+                    .iter()
+                    .filter(|weapon| economy::is_grenade(weapon))
+                    .count() as u32
+            })
+            .unwrap_or_default()
+    }
+
+    /// The scoreboard, best first, by rating if any rounds have been played or else by kills
+    pub fn scoreboard(&self) -> Vec<&PlayerScore> {
+        let mut scoreboard = self.ranking();
+        scoreboard.sort_by(|a, b| {
+            b.rating
+                .partial_cmp(&a.rating)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        scoreboard
+    }
+
+    /// Add the round that just ended to everyone's score, choosing its MVP
+    fn score_round(&mut self) {
+        let bomb_player = self
+            .bomb_plants
+            .iter()
+            .rev()
+            .find(|plant| plant.round == self.round_number)
+            .and_then(|plant| match plant.outcome {
+                Some(BombOutcome::Exploded) => Some(plant.planter.clone()),
+                Some(BombOutcome::Defused) => plant.defuser.clone(),
+                _ => None,
+            });
+
+        let (scores, mvp, clutch) = match self.current_round_mut() {
+            Some(round) => {
+                round.choose_mvp(bomb_player);
+                (round.scores(), round.mvp.clone(), round.clutch.clone())
+            }
+            None => return,
+        };
+
+        for round_score in scores {
+            let player = match self.player_on_team(&round_score.player_id) {
+                Some(player) => player,
+                None => continue,
+            };
+            let unused_utility = if round_score.survived {
+                self.grenades_held(&round_score.player_id)
+            } else {
+                0
+            };
+
+            let score = self.player_score_mut(player);
+            score.rounds_played += 1;
+            score.unused_utility_at_round_end += unused_utility;
+            if round_score.kast {
+                score.kast_rounds += 1;
+            }
+            if round_score.kills > 0 {
+                score.multi_kills[round_score.kills.min(5) - 1] += 1;
+            }
+            if mvp.as_deref() == Some(round_score.player_id.as_str()) {
+                score.mvps += 1;
+            }
+            if let Some(clutch) = clutch
+                .as_ref()
+                .filter(|clutch| clutch.player == round_score.player_id)
+            {
+                score.clutches += 1;
+                if clutch.won == Some(true) {
+                    score.clutches_won += 1;
+                }
+            }
+            score.update_averages();
+        }
+    }
+
+    fn player_on_team(&self, player_id: &str) -> Option<Player> {
+        self.cts
+            .players
+            .iter()
+            .chain(self.ts.players.iter())
+            .find(|player| player.id == player_id)
+            .cloned()
+    }
+
+    fn player_score_mut(&mut self, player: Player) -> &mut PlayerScore {
+        if let Some(index) = self
+            .scores
+            .iter()
+            .position(|score| score.player.id == player.id)
+        {
+            &mut self.scores[index]
+        } else {
+            self.scores.push(PlayerScore::new(player));
+            self.scores.last_mut().unwrap()
+        }
+    }
+
+    /// The players ranked by kills, with fewer deaths breaking ties (see `PlayerScore::ranking_key`).
+    /// This is only an approximation for the free-for-all modes, as arms race is really won by whoever gets through the weapons first, and deathmatch by points, neither of which is logged
+    pub fn ranking(&self) -> Vec<&PlayerScore> {
+        let mut ranking: Vec<&PlayerScore> = self.scores.iter().collect();
+        ranking.sort_by_key(|score| score.ranking_key());
+        ranking
+    }
+
+    /// How the match turned out, once it's over
+    pub fn result(&self) -> Option<&MatchResult> {
+        self.result.as_ref()
+    }
+
+    pub fn set_result(&mut self, result: MatchResult) {
+        self.result = Some(result);
+    }
+
+    /// Which half of the match it is, counting from 1. Overtime halves carry on from the regulation ones
+    pub fn half(&self) -> usize {
+        self.half + 1
+    }
+
+    /// Put each player into the logical team that's playing on their side
+    fn update_team_players(&mut self) {
+        for team in self.teams.iter_mut() {
+            let side = if team.side == "CT" {
+                &self.cts
+            } else {
+                &self.ts
+            };
+            team.players = side
+                .players
+                .iter()
+                .map(|player| player.id.clone())
+                .collect();
+        }
+    }
+
+    /// The teams swap sides, and the next half begins once the next round starts.
+    /// Everyone starts the new half from scratch, with the starting money and the default weapons for their new side
+    fn begin_halftime(&mut self) {
+        self.phase = MatchPhase::Halftime;
+        self.half += 1;
+
+        for team in self.teams.iter_mut() {
+            team.swap_side();
+            team.loss_streak = 0;
+        }
+
+        let start_money = self.start_money();
+        for state in self.live.values_mut() {
+            state.money = start_money;
+            state.alive = false;
+        }
+    }
+
+    fn start_money(&self) -> i64 {
+        self.rules
+            .cvar("mp_startmoney")
+            .and_then(|money| money.parse().ok())
+            .unwrap_or(economy::START_MONEY)
+    }
+
+    fn max_money(&self) -> i64 {
+        self.rules
+            .cvar("mp_maxmoney")
+            .and_then(|money| money.parse().ok())
+            .unwrap_or(economy::MAX_MONEY)
+    }
+
+    /// Which side the player is on, if they're on one
+    fn side_of(&self, player_id: &str) -> Option<&'static str> {
+        if self.cts.players.iter().any(|player| player.id == player_id) {
+            Some("CT")
+        } else if self.ts.players.iter().any(|player| player.id == player_id) {
+            Some("TERRORIST")
+        } else {
+            None
+        }
+    }
+
+    /// The live state of every player we've seen in the match, keyed by player ID
+    pub fn live_players(&self) -> &HashMap<String, PlayerState> {
+        &self.live
+    }
+
+    pub fn live_player(&self, player_id: &str) -> Option<&PlayerState> {
+        self.live.get(player_id)
+    }
+
+    /// The live state of the player, starting them off with the starting money and default weapons if they're new
+    fn live_player_mut(&mut self, player_id: &str) -> &mut PlayerState {
+        let side = self.side_of(player_id).unwrap_or_default();
+        let start_money = self.start_money();
+
+        self.live
+            .entry(player_id.to_owned())
+            .or_insert_with(|| PlayerState::new(side, start_money))
+    }
+
+    /// The player was hit, leaving them with the given health and armor. Returns how much health they lost
+    pub fn player_hurt(&mut self, player_id: &str, health: u32, armor: u32) -> u32 {
+        let state = self.live_player_mut(player_id);
+        let health_lost = state.health.saturating_sub(health);
+
+        state.health = health;
+        state.armor = armor;
+        if armor == 0 {
+            state.helmet = false;
+        }
+
+        health_lost
+    }
+
+    /// The player bought the item, returning what it cost, if we know
+    pub fn purchase(&mut self, player_id: &str, item: &str) -> Option<i64> {
+        let price = economy::price(item);
+        let state = self.live_player_mut(player_id);
+
+        state.add_item(item);
+        if let Some(price) = price {
+            state.money = (state.money - price).max(0);
+        }
+
+        price
+    }
+
+    /// The player left the buyzone with exactly these items, e.g. `weapon_knife`, `weapon_glock`, `kevlar(100)` and `helmet`
+    pub fn left_buyzone(&mut self, player_id: &str, items: &[&str]) {
+        let state = self.live_player_mut(player_id);
+
+        state.weapons.clear();
+        state.armor = 0;
+        state.helmet = false;
+
+        for item in items {
+            if let Some(armor) = item
+                .strip_prefix("kevlar(")
+                .and_then(|armor| armor.strip_suffix(')'))
+            {
+                state.armor = armor.parse().unwrap_or_default();
+            } else if *item == "helmet" {
+                state.helmet = true;
+            } else {
+                state.weapons.push(economy::item_name(item).to_owned());
+            }
+        }
+    }
+
+    pub fn pick_up(&mut self, player_id: &str, item: &str) {
+        self.live_player_mut(player_id).add_item(item);
+    }
+
+    /// The server told us exactly how much money the player has
+    pub fn set_money(&mut self, player_id: &str, money: i64) {
+        self.live_player_mut(player_id).money = money;
+    }
+
+    pub fn round_number(&self) -> u32 {
+        self.round_number
+    }
+
+    /// Move on to the next round, returning its number. Coming back from halftime, the match is live again, or in overtime once the regulation rounds have been played
+    pub fn start_round(&mut self, epoch: i64) -> u32 {
+        self.round_number += 1;
+        self.between_rounds = false;
+        self.first_hits.clear();
+
+        let ids = |team: &Team| {
+            team.players
+                .iter()
+                .map(|player| player.id.clone())
+                .collect()
+        };
+        let mut round = Round::new(self.round_number, epoch, ids(&self.cts), ids(&self.ts));
+        round.events = self.events.len()..self.events.len();
+        self.rounds.push(round);
+
+        // Players who swapped sides between rounds have swapped teams too, unless it was halftime
+        self.update_team_players();
+
+        // Everyone on a team spawns
+        let ids: Vec<String> = self
+            .cts
+            .players
+            .iter()
+            .chain(self.ts.players.iter())
+            .map(|player| player.id.clone())
+            .collect();
+
+        for id in ids {
+            let side = self.side_of(&id).unwrap_or_default();
+            self.live_player_mut(&id).spawn(side);
+        }
+
+        if matches!(self.phase, MatchPhase::Live | MatchPhase::Halftime) {
+            self.phase = match self.rules.max_rounds {
+                Some(max_rounds)
+                    if self.rules.overtime != Some(false) && self.round_number > max_rounds =>
+                {
+                    MatchPhase::Overtime
+                }
+                _ => MatchPhase::Live,
+            };
+        }
+
+        self.round_number
+    }
+
+    /// Finish the current round, returning true if it was the last round of a half
+    pub fn end_round(&mut self, epoch: i64) -> bool {
+        self.between_rounds = true;
+        self.side_swaps = 0;
+
+        if let Some(round) = self.current_round_mut() {
+            round.end_epoch = Some(epoch);
+        }
+
+        self.score_round();
+
+        if self.is_last_round_of_half() {
+            self.begin_halftime();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the current round is the last of a half, going by the server rules. Overtime is played in halves too
+    fn is_last_round_of_half(&self) -> bool {
+        let max_rounds = match self.rules.max_rounds {
+            Some(max_rounds) => max_rounds,
+            None => return false,
+        };
+
+        match self.phase {
+            MatchPhase::Live => self.round_number == max_rounds / 2,
+            MatchPhase::Overtime => {
+                let overtime_max_rounds = self.rules.overtime_max_rounds.unwrap_or(6).max(2);
+                let overtime_round = self.round_number - max_rounds;
+                overtime_round % overtime_max_rounds == overtime_max_rounds / 2
+            }
+            _ => false,
+        }
+    }
+
+    /// Note a player swapping sides between rounds, returning true if it looks like halftime, i.e. most of the players have swapped. This catches halftime when the server rules weren't logged
+    pub fn swap_side(&mut self) -> bool {
+        if !self.between_rounds || !matches!(self.phase, MatchPhase::Live | MatchPhase::Overtime) {
+            return false;
+        }
+
+        self.side_swaps += 1;
+
+        let roster_size = self.cts.players.len() + self.ts.players.len();
+        if self.side_swaps >= 2 && self.side_swaps * 2 >= roster_size {
+            self.begin_halftime();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn rounds(&self) -> &Vec<Round> {
+        &self.rounds
+    }
+
+    /// The round being played, or the one that's just finished if it's between rounds
+    pub fn current_round(&self) -> Option<&Round> {
+        self.rounds.last()
+    }
+
+    pub fn current_round_mut(&mut self) -> Option<&mut Round> {
+        self.rounds.last_mut()
+    }
+
+    /// Take the player out of the players alive in the current round, returning the clutch that began if that left a teammate on their own
+    pub fn player_died(&mut self, player_id: &str, epoch: i64) -> Option<Clutch> {
+        // Dying in the warmup or between rounds doesn't waste anything
+        let in_progress = self.current_round().is_some_and(Round::in_progress);
+        let grenades = self.grenades_held(player_id);
+        if let Some(player) = self
+            .player_on_team(player_id)
+            .filter(|_| in_progress && grenades > 0)
+        {
+            self.player_score_mut(player).unused_utility_at_death += grenades;
+        }
+
+        self.live_player_mut(player_id).die();
+
+        // Whoever hit them before they died has to start again once they respawn
+        self.first_hits.retain(|(_, victim), _| victim != player_id);
+
+        self.current_round_mut()
+            .filter(|round| round.in_progress())
+            .and_then(|round| round.player_died(player_id, epoch))
+            .cloned()
+    }
+
+    pub fn plant_bomb(&mut self, site: Option<String>, planter: String, planted_epoch: i64) {
+        let plant = BombPlant::new(self.round_number, site, planter, planted_epoch);
+        self.bomb_plants.push(plant);
+    }
+
+    /// The bomb plant in the current round that hasn't been resolved yet, if there is one
+    pub fn active_bomb_plant_mut(&mut self) -> Option<&mut BombPlant> {
+        let round_number = self.round_number;
+
+        self.bomb_plants
+            .iter_mut()
+            .rev()
+            .find(|plant| plant.round == round_number && plant.outcome.is_none())
+    }
+
+    /// What has happened to the hostages in the current round, if anything has
+    pub fn hostage_status(&self) -> Option<&HostageStatus> {
+        self.hostages
+            .iter()
+            .find(|status| status.round == self.round_number)
+    }
+
+    /// What has happened to the hostages in the current round, starting a new status for the round if need be
+    pub fn hostage_status_mut(&mut self) -> &mut HostageStatus {
+        let round_number = self.round_number;
+
+        if let Some(index) = self
+            .hostages
+            .iter()
+            .position(|status| status.round == round_number)
+        {
+            &mut self.hostages[index]
+        } else {
+            self.hostages.push(HostageStatus::new(round_number));
+            self.hostages.last_mut().unwrap()
+        }
+    }
+
+    /// Record a suicide, which counts as a death, returning what it was caused by
+    pub fn add_suicide(
+        &mut self,
+        epoch: i64,
+        player: Player,
+        position: String,
+        weapon: String,
+    ) -> SuicideCause {
+        let suicide = Suicide::new(
+            self.round_number,
+            epoch,
+            player.id.clone(),
+            position,
+            weapon,
+        );
+        let cause = suicide.cause();
+        self.suicides.push(suicide);
+        self.record_death(player);
+        cause
+    }
+
+    /// Record the blinding, adding it to the flash stats of the player who threw the flashbang
+    pub fn add_blind(
+        &mut self,
+        epoch: i64,
+        attacker: Player,
+        victim: String,
+        duration: f64,
+        team_flash: bool,
+        entindex: u32,
+    ) {
+        let blind = Blind::new(
+            self.round_number,
+            epoch,
+            attacker.id.clone(),
+            victim,
+            duration,
+            team_flash,
+            entindex,
+        );
+        self.blinds.push(blind);
+
+        let score = self.player_score_mut(attacker);
+        if team_flash {
+            score.team_flashes += 1;
+            score.team_blind_time = round_to_hundredths(score.team_blind_time + duration);
+        } else {
+            score.enemies_flashed += 1;
+            score.enemy_blind_time = round_to_hundredths(score.enemy_blind_time + duration);
+        }
+    }
+
+    /// The teammate of the killer who flashed the victim, if the victim was still blind when they were killed.
+    /// Only blinds from the current round count, and there are none from before a restart, as `reset` clears them
+    fn flash_assister(&self, kill: &Kill) -> Option<String> {
+        self.blinds
+            .iter()
+            .rev()
+            .filter(|blind| blind.round == self.round_number)
+            .find(|blind| {
+                blind.victim == kill.victim
+                    && !blind.team_flash
+                    && blind.attacker != kill.killer
+                    && kill.epoch >= blind.epoch
+                    && (kill.epoch - blind.epoch) as f64 <= blind.duration.ceil()
+            })
+            .map(|blind| blind.attacker.clone())
+    }
+
+    pub fn events(&self) -> &Vec<Event> {
+        &self.events
+    }
+
+    /// The events that happened during the round
+    pub fn round_events(&self, round: &Round) -> &[Event] {
+        &self.events[round.events.clone()]
+    }
+
+    /// Add the event to the match, and to the round if one is being played. A round's events are always the latest ones in the match while it's being played, so it just keeps track of where they are
+    pub fn add_event(&mut self, event: Event) {
+        let index = self.events.len();
+        if let Some(round) = self.current_round_mut().filter(|round| round.in_progress()) {
+            round.events.end = index + 1;
+        }
+
+        self.events.push(event);
+    }
+
+    pub fn players(&self) -> &HashMap<String, PlayerIdentity> {
+        &self.players
+    }
+
+    /// Note that the player was seen with the given userid and name, returning their previous name if they've changed it.
+    /// The player's name is updated in their team too
+    pub fn see_player(
+        &mut self,
+        id: &str,
+        steam_id: &str,
+        userid: &str,
+        name: &str,
+        epoch: i64,
+    ) -> Option<String> {
+        let identity = self
+            .players
+            .entry(id.to_owned())
+            .or_insert_with(|| PlayerIdentity::new(id, steam_id, userid, name, epoch));
+
+        if identity.userid != userid {
+            identity.userid = userid.to_owned();
+        }
+
+        if identity.name() == name {
+            return None;
+        }
+
+        let previous_name = identity.name().to_owned();
+        identity.names.push(NameUse {
+            name: name.to_owned(),
+            epoch,
+        });
+
+        for player in self
+            .cts
+            .players
+            .iter_mut()
+            .chain(self.ts.players.iter_mut())
+        {
+            if player.id == id {
+                player.name = name.to_owned();
+            }
+        }
+
+        Some(previous_name)
+    }
+
+    pub fn add_player_to_team(&mut self, new_player: Player, team_name: &str) {
+        // First, remove the player if they're already in a team
+        if self.cts().players.contains(&new_player) {
+            self.cts_mut()
+                .players
+                .retain(|player| player != &new_player); // i.e. retain all the current players that are NOT equal to new_player
+        } else if self.ts().players.contains(&new_player) {
+            self.ts_mut().players.retain(|player| player != &new_player); // i.e. retain all the current players that are NOT equal to new_player
+        }
+
+        // Second, add the new player to their team
+        match team_name {
+            "CT" => {
+                log::info!("Adding player {:?} to team {}", &new_player, &team_name);
+                self.cts_mut().players.push(new_player);
+            }
+            "TERRORIST" => {
+                log::info!("Adding player {:?} to team {}", &new_player, &team_name);
+                self.ts_mut().players.push(new_player);
+            }
+            _ => {
+                log::warn!("Unknown team: {}", team_name)
+            }
+        }
+
+        log::info!("CT: {:?}", self.cts());
+        log::info!("TERRORIST: {:?}", self.ts());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(id: &str) -> Player {
+        Player::new(id.to_owned(), id.to_owned(), false)
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    /// An AK-47 body shot kill, which can be tweaked with struct update syntax
+    fn kill(epoch: i64, killer: &str, victim: &str) -> Kill {
+        Kill::new(
+            epoch,
+            killer.to_owned(),
+            "0 0 0".to_owned(),
+            victim.to_owned(),
+            "0 0 0".to_owned(),
+            "ak47".to_owned(),
+            false,
+            false,
+        )
+    }
+
+    fn score<'a>(state: &'a GameState, id: &str) -> &'a PlayerScore {
+        state
+            .scores
+            .iter()
+            .find(|score| score.player.id == id)
+            .unwrap()
+    }
+
+    /// A live match with the given players on each side
+    fn live_game(cts: &[&str], ts: &[&str]) -> GameState {
+        let mut state = GameState::new();
+        for (ids, side) in [(cts, "CT"), (ts, "TERRORIST")].iter() {
+            for id in ids.iter() {
+                state.add_player_to_team(player(id), side);
+            }
+        }
+        state.set_phase(MatchPhase::Live);
+        state
+    }
+
+    /// Play the given number of rounds, returning the numbers of those that were the last of a half
+    fn play_rounds(state: &mut GameState, rounds: u32) -> Vec<u32> {
+        (0..rounds)
+            .filter_map(|_| {
+                let round = state.start_round(0);
+                if state.end_round(0) {
+                    Some(round)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_scoreboard_averages() {
+        let player = Player::new("Alice".to_owned(), "STEAM_1:1:00000001".to_owned(), false);
+        let mut score = PlayerScore::new(player);
+
+        // Nothing played yet
+        score.update_averages();
+        assert_eq!(score.adr(), 0.0);
+        assert_eq!(score.rating(), 0.0);
+
+        score.kills = 4;
+        score.headshot_kills = 1;
+        score.deaths = 2;
+        score.damage = 420;
+        score.rounds_played = 3;
+        score.kast_rounds = 2;
+        score.multi_kills = [2, 1, 0, 0, 0];
+        score.update_averages();
+
+        assert_eq!(score.adr(), 140.0);
+        assert_eq!(score.headshot_percentage(), 25.0);
+        assert_eq!(score.kast(), 66.67);
+        assert_eq!(score.rating(), 1.58);
+    }
+
+    #[test]
+    fn test_clutch() {
+        let mut round = Round::new(1, 0, ids(&["a", "b", "c"]), ids(&["x", "y"]));
+
+        // 2v2 isn't a clutch
+        assert!(round.player_died("a", 10).is_none());
+
+        // 1v2 is
+        let clutch = round.player_died("b", 20).unwrap();
+        assert_eq!(clutch.player(), "c");
+        assert_eq!(clutch.side(), "CT");
+        assert_eq!(clutch.opponents(), 2);
+
+        // The enemy being left on their own too doesn't make it their clutch
+        assert!(round.player_died("x", 30).is_none());
+
+        round.set_result("CT", None, "CTs_Win", 1, 0);
+        assert_eq!(round.clutch().unwrap().won(), Some(true));
+    }
+
+    #[test]
+    fn test_trade() {
+        let mut round = Round::new(1, 0, ids(&["a", "b"]), ids(&["x", "y"]));
+
+        assert!(round.add_kill(kill(10, "x", "a"), 5).is_none());
+        assert_eq!(round.opening_kill().unwrap().victim(), "a");
+
+        // Too slow to be a trade
+        assert!(round.add_kill(kill(20, "b", "x"), 5).is_none());
+
+        let mut round = Round::new(1, 0, ids(&["a", "b"]), ids(&["x", "y"]));
+        round.add_kill(kill(10, "x", "a"), 5);
+
+        let trade = round.add_kill(kill(13, "b", "x"), 5).unwrap();
+        assert_eq!(trade.player(), "b");
+        assert_eq!(trade.traded(), "a");
+        assert_eq!(trade.seconds(), 3);
+        assert!(round.traded("a"));
+        assert!(!round.traded("x"));
+    }
+
+    #[test]
+    fn test_weapon_stats() {
+        assert_eq!(hitgroup_name("left arm"), "arms");
+        assert_eq!(hitgroup_name("right leg"), "legs");
+        assert_eq!(hitgroup_name("head"), "head");
+
+        let mut stats = WeaponStats::default();
+        stats.add_time_to_kill(1);
+        stats.add_time_to_kill(2);
+        assert_eq!(stats.time_to_kill(), 1.5);
+    }
+
+    #[test]
+    fn test_damage_matrix() {
+        let mut matrix = DamageMatrix::default();
+        matrix.add("a", "x", 27, false);
+        matrix.add("a", "x", 73, false);
+        matrix.add("b", "x", 10, false);
+        matrix.add("a", "b", 5, true);
+
+        let damage = matrix.enemy().between("a", "x").unwrap();
+        assert_eq!(damage.hits(), 2);
+        assert_eq!(damage.damage(), 100);
+
+        assert_eq!(matrix.enemy().taken_by("x").len(), 2);
+        assert_eq!(matrix.enemy().given_by("a").len(), 1);
+        assert!(matrix.enemy().between("a", "b").is_none());
+        assert_eq!(matrix.friendly().between("a", "b").unwrap().damage(), 5);
+    }
+
+    #[test]
+    fn test_set_cvar() {
+        let mut rules = Rules::new();
+        rules.set_cvar("mp_maxrounds", "30");
+        rules.set_cvar("mp_overtime_enable", "1");
+        rules.set_cvar("mp_overtime_maxrounds", "6");
+        rules.set_cvar("mp_friendlyfire", "0");
+        rules.set_cvar("sv_cheats", "0");
+
+        assert_eq!(rules.max_rounds, Some(30));
+        assert_eq!(rules.overtime, Some(true));
+        assert_eq!(rules.overtime_max_rounds, Some(6));
+        assert_eq!(rules.friendly_fire, Some(false));
+        assert_eq!(rules.cvar("sv_cheats"), Some("0"));
+        assert_eq!(rules.cvar("mp_maxrounds"), Some("30"));
+        assert_eq!(rules.cvar("mp_startmoney"), None);
+
+        // A value that isn't a number doesn't count
+        rules.set_cvar("mp_maxrounds", "lots");
+        assert_eq!(rules.max_rounds, None);
+    }
+
+    #[test]
+    fn test_hostage_status() {
+        let mut state = GameState::new();
+        assert!(state.hostage_status().is_none());
+
+        state.start_round(0);
+        state.hostage_status_mut().pick_up("a".to_owned());
+        state.hostage_status_mut().pick_up("b".to_owned());
+        state.hostage_status_mut().drop_off("b");
+        state.hostage_status_mut().rescue("a".to_owned());
+        state.hostage_status_mut().kill("x".to_owned());
+
+        let status = state.hostage_status().unwrap();
+        assert_eq!(status.pickups, 2);
+        assert!(status.carriers.is_empty());
+        assert_eq!(status.rescued_by(), &vec!["a".to_owned()]);
+        assert_eq!(status.killed_by(), &vec!["x".to_owned()]);
+
+        // Each round starts with a clean slate
+        state.start_round(100);
+        assert!(state.hostage_status().is_none());
+        state.hostage_status_mut().rescue("b".to_owned());
+        assert_eq!(
+            state.hostage_status().unwrap().rescued_by(),
+            &vec!["b".to_owned()]
+        );
+        assert_eq!(state.hostages.len(), 2);
+        assert_eq!(state.hostages[0].rescued_by(), &vec!["a".to_owned()]);
+    }
+
+    #[test]
+    fn test_regulation_halftime() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.rules_mut().set_cvar("mp_maxrounds", "30");
+
+        assert_eq!(play_rounds(&mut state, 15), vec![15]);
+        assert_eq!(state.phase(), MatchPhase::Halftime);
+        assert_eq!(state.half(), 2);
+        assert_eq!(state.team_on_side("CT").unwrap().name(), "Team B");
+
+        state.start_round(0);
+        assert_eq!(state.phase(), MatchPhase::Live);
+        assert!(!state.end_round(0));
+    }
+
+    #[test]
+    fn test_overtime_halves() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.rules_mut().set_cvar("mp_maxrounds", "4");
+        state.rules_mut().set_cvar("mp_overtime_enable", "1");
+        state.rules_mut().set_cvar("mp_overtime_maxrounds", "6");
+
+        // Halftime in regulation, then halfway through each overtime
+        assert_eq!(play_rounds(&mut state, 16), vec![2, 7, 13]);
+        assert_eq!(state.phase(), MatchPhase::Overtime);
+        assert_eq!(state.half(), 4);
+
+        // Overtime carries on after its halftimes
+        assert_eq!(play_rounds(&mut state, 3), vec![19]);
+        state.start_round(0);
+        assert_eq!(state.phase(), MatchPhase::Overtime);
+    }
+
+    #[test]
+    fn test_restart() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.rules_mut().set_cvar("mp_maxrounds", "2");
+        state.rename_team(1, "Apples");
+
+        state.start_round(0);
+        state.win_round("CT", "CTs_Win");
+        state.end_round(0);
+        assert_eq!(state.phase(), MatchPhase::Halftime);
+
+        state.reset();
+        assert_eq!(state.round_number(), 0);
+        assert!(state.rounds().is_empty());
+        assert_eq!(state.half(), 1);
+
+        // The teams are back on their original sides with nothing won, but keep their names, players and the server rules
+        let team = state.team_on_side("CT").unwrap();
+        assert_eq!(team.name(), "Apples");
+        assert_eq!(team.score(), 0);
+        assert_eq!(team.players, vec!["a".to_owned()]);
+        assert_eq!(state.rules().max_rounds, Some(2));
+
+        // The phase is left for the restart's caller to decide
+        assert_eq!(state.phase(), MatchPhase::Halftime);
+    }
+
+    #[test]
+    fn test_side_swap_heuristic() {
+        // No mp_maxrounds, so halftime can only be spotted by the players swapping sides
+        let mut state = live_game(&["a", "b"], &["x", "y"]);
+        assert!(state.rules().max_rounds.is_none());
+
+        state.start_round(0);
+
+        // Swapping sides during a round isn't halftime
+        assert!(!state.swap_side());
+        assert!(!state.swap_side());
+        assert!(!state.end_round(0));
+
+        // One player swapping between rounds isn't either
+        assert!(!state.swap_side());
+        state.start_round(0);
+        state.end_round(0);
+
+        // But half of the players swapping is
+        assert!(!state.swap_side());
+        assert!(state.swap_side());
+        assert_eq!(state.phase(), MatchPhase::Halftime);
+        assert_eq!(state.half(), 2);
+
+        // Nor does it happen twice for the same halftime
+        assert!(!state.swap_side());
+    }
+
+    #[test]
+    fn test_logical_teams() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.rules_mut().set_cvar("mp_maxrounds", "4");
+
+        // Team A wins both rounds of the first half on CT, then one of the second half on T
+        for side in ["CT", "CT", "TERRORIST", "CT"].iter() {
+            state.start_round(0);
+            state.win_round(side, "Target_Bombed");
+            state.end_round(0);
+        }
+
+        let team_a = &state.teams[0];
+        assert_eq!(team_a.name(), "Team A");
+        assert_eq!(team_a.side, "TERRORIST");
+        assert_eq!(team_a.score(), 3);
+        assert_eq!(team_a.half_scores(), &vec![2, 1]);
+        assert_eq!(team_a.rounds_won_as_ct, 2);
+        assert_eq!(team_a.rounds_won_as_t, 1);
+
+        let team_b = &state.teams[1];
+        assert_eq!(team_b.side, "CT");
+        assert_eq!(team_b.score(), 1);
+        assert_eq!(team_b.half_scores(), &vec![0, 1]);
+        assert_eq!(team_b.rounds_won_as_ct, 1);
+        assert_eq!(team_b.rounds_won_as_t, 0);
+
+        // The players follow their team to its new side once they've switched
+        state.add_player_to_team(player("a"), "TERRORIST");
+        state.add_player_to_team(player("x"), "CT");
+        state.start_round(0);
+        assert_eq!(state.teams[0].players, vec!["a".to_owned()]);
+        assert_eq!(
+            state.team_on_side("CT").unwrap().players,
+            vec!["x".to_owned()]
+        );
+
+        // Renaming goes by the side the team started on, not the one it's on now
+        state.rename_team(1, "Apples");
+        state.rename_team(2, "Oranges");
+        state.rename_team(3, "Pears");
+        assert_eq!(state.team_on_side("TERRORIST").unwrap().name(), "Apples");
+        assert_eq!(state.team_on_side("CT").unwrap().name(), "Oranges");
+
+        // Clan names go by the side the team is on now, and survive a restart
+        state.set_clan_name("CT", "Bananas");
+        assert_eq!(state.teams[1].clan_name(), Some("Bananas"));
+        assert_eq!(state.teams[1].name(), "Bananas");
+        assert_eq!(state.teams[0].clan_name(), None);
+
+        state.reset();
+        assert_eq!(state.teams[1].clan_name(), Some("Bananas"));
+        assert_eq!(state.team_on_side("TERRORIST").unwrap().name(), "Bananas");
+    }
+
+    #[test]
+    fn test_record_kill() {
+        let mut state = live_game(&["a", "b"], &["x"]);
+
+        state.start_round(0);
+        let (trade, opening) = state.record_kill(
+            player("a"),
+            player("x"),
+            Kill {
+                headshot: true,
+                ..kill(0, "a", "x")
+            },
+        );
+        assert!(trade.is_none());
+        assert!(opening);
+
+        let (_, opening) = state.record_kill(
+            player("x"),
+            player("b"),
+            Kill {
+                weapon: "glock".to_owned(),
+                ..kill(0, "x", "b")
+            },
+        );
+        assert!(!opening);
+
+        // Neither a team kill nor a suicide counts as a kill, but both count as a death
+        state.record_kill(
+            player("a"),
+            player("b"),
+            Kill {
+                team_kill: true,
+                ..kill(0, "a", "b")
+            },
+        );
+        state.record_kill(
+            player("x"),
+            player("x"),
+            Kill {
+                weapon: "hegrenade".to_owned(),
+                ..kill(0, "x", "x")
+            },
+        );
+
+        assert_eq!(score(&state, "a").kills(), 1);
+        assert_eq!(score(&state, "a").headshot_kills, 1);
+        assert_eq!(score(&state, "a").weapons["ak47"].kills, 1);
+        assert_eq!(score(&state, "x").kills(), 1);
+        assert_eq!(score(&state, "x").deaths(), 2);
+        assert_eq!(score(&state, "b").deaths(), 2);
+        assert_eq!(score(&state, "a").opening_kills, 1);
+        assert_eq!(score(&state, "x").opening_deaths, 1);
+
+        // Paid for the kill, but fined for the team kill
+        assert_eq!(
+            state.live_player("a").unwrap().money(),
+            economy::START_MONEY + economy::kill_reward("ak47") + economy::TEAM_KILL_PENALTY
+        );
+    }
+
+    #[test]
+    fn test_ranking() {
+        let mut state = GameState::new();
+        for (id, kills, deaths) in [("a", 10, 5), ("b", 10, 3), ("c", 12, 9), ("d", 10, 3)].iter() {
+            let score = state.player_score_mut(player(id));
+            score.kills = *kills;
+            score.deaths = *deaths;
+        }
+
+        let ranking: Vec<&str> = state
+            .ranking()
+            .iter()
+            .map(|score| score.player().id())
+            .collect();
+        assert_eq!(ranking, vec!["c", "b", "d", "a"]);
+
+        // Level on kills isn't level, unless it's on deaths too
+        let ranking = state.ranking();
+        assert!(ranking[1].ranking_key() < ranking[3].ranking_key());
+        assert_eq!(ranking[1].ranking_key(), ranking[2].ranking_key());
+    }
+
+    #[test]
+    fn test_round() {
+        let mut round = Round::new(3, 100, ids(&["a", "b"]), ids(&["x", "y"]));
+
+        assert_eq!(round.number(), 3);
+        assert_eq!(round.start_epoch(), 100);
+        assert!(round.in_progress());
+        assert!(round.winner().is_none());
+        assert_eq!(round.alive(), (&ids(&["a", "b"]), &ids(&["x", "y"])));
+
+        round.set_result(
+            "TERRORIST",
+            Some("Team B".to_owned()),
+            "Target_Bombed",
+            0,
+            3,
+        );
+        assert_eq!(round.winner(), Some("TERRORIST"));
+        assert_eq!(round.winner_team.as_deref(), Some("Team B"));
+        assert_eq!(round.reason(), Some("Target_Bombed"));
+        assert_eq!((round.ct_score, round.terrorist_score), (Some(0), Some(3)));
+    }
+
+    #[test]
+    fn test_alive_snapshots() {
+        let mut round = Round::new(1, 0, ids(&["a", "b"]), ids(&["x", "y"]));
+
+        round.player_died("a", 10);
+        round.player_died("y", 20);
+        assert_eq!(round.alive(), (&ids(&["b"]), &ids(&["x"])));
+
+        // Someone who isn't alive (e.g. a spectator, or someone who already died) doesn't change anything
+        round.player_died("a", 30);
+        round.player_died("s", 30);
+
+        // Each death is a snapshot of who was left, at the time of the death
+        let snapshots: Vec<(i64, usize, usize)> = round
+            .alive
+            .iter()
+            .map(|alive| (alive.epoch, alive.ct.len(), alive.terrorist.len()))
+            .collect();
+        assert_eq!(snapshots, vec![(0, 2, 2), (10, 1, 2), (20, 1, 1)]);
+    }
+
+    #[test]
+    fn test_round_events() {
+        let mut state = live_game(&["a"], &["x"]);
+        let event = |epoch| Event::new(epoch, HashMap::new());
+
+        // Before the first round
+        state.add_event(event(0));
+
+        state.start_round(10);
+        state.add_event(event(11));
+        state.add_event(event(12));
+        state.end_round(13);
+
+        // Between rounds
+        state.add_event(event(14));
+
+        state.start_round(20);
+        state.add_event(event(21));
+
+        let epochs = |events: &[Event]| events.iter().map(|event| event.epoch).collect::<Vec<_>>();
+        assert_eq!(epochs(state.events()), vec![0, 11, 12, 14, 21]);
+        assert_eq!(epochs(state.round_events(&state.rounds()[0])), vec![11, 12]);
+        assert_eq!(epochs(state.round_events(&state.rounds()[1])), vec![21]);
+    }
+
+    #[test]
+    fn test_player_state() {
+        let mut state = PlayerState::new("CT", 800);
+        assert_eq!(state.weapons(), &economy::default_weapons("CT"));
+
+        state.add_item("item_assaultsuit");
+        state.add_item("weapon_famas");
+        assert_eq!((state.armor(), state.helmet()), (100, true));
+        assert!(state.weapons().contains(&"famas".to_owned()));
+
+        // Surviving the round keeps everything
+        state.health = 20;
+        state.spawn("CT");
+        assert_eq!(state.health(), 100);
+        assert!(state.weapons().contains(&"famas".to_owned()));
+        assert!(state.helmet());
+
+        // Dying loses everything
+        state.die();
+        assert!(!state.alive());
+        assert_eq!(state.health(), 0);
+        assert!(state.weapons().is_empty());
+        state.spawn("TERRORIST");
+        assert!(state.alive());
+        assert_eq!(state.armor(), 0);
+        assert_eq!(state.weapons(), &economy::default_weapons("TERRORIST"));
+    }
+
+    #[test]
+    fn test_buying() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.start_round(0);
+
+        assert_eq!(
+            state.purchase("a", "weapon_deagle"),
+            economy::price("deagle")
+        );
+        assert_eq!(
+            state.live_player("a").unwrap().money(),
+            economy::START_MONEY - economy::price("deagle").unwrap()
+        );
+
+        // You can't go into debt, even if we've got the prices wrong
+        state.purchase("a", "ak47");
+        assert_eq!(state.live_player("a").unwrap().money(), 0);
+
+        // Leaving the buyzone says exactly what the player has
+        state.left_buyzone(
+            "x",
+            &["weapon_knife_t", "weapon_glock", "kevlar(100)", "helmet"],
+        );
+        let x = state.live_player("x").unwrap();
+        assert_eq!(x.weapons(), &vec!["knife_t".to_owned(), "glock".to_owned()]);
+        assert_eq!((x.armor(), x.helmet()), (100, true));
+    }
+
+    #[test]
+    fn test_money_flow() {
+        let mut state = live_game(&["a"], &["x"]);
+        state.rules_mut().set_cvar("mp_maxrounds", "30");
+        state.rules_mut().set_cvar("mp_maxmoney", "20000");
+        let money = |state: &GameState, id| state.live_player(id).unwrap().money();
+
+        state.start_round(0);
+        state.record_kill(
+            player("a"),
+            player("x"),
+            Kill {
+                weapon: "usp_silencer".to_owned(),
+                ..kill(0, "a", "x")
+            },
+        );
+        assert_eq!(money(&state, "a"), 800 + 300);
+
+        state.win_round("CT", "CTs_Win");
+        assert_eq!(money(&state, "a"), 800 + 300 + 3250);
+        assert_eq!(money(&state, "x"), 800 + 1400);
+        state.end_round(0);
+
+        // The loss bonus goes up with each loss...
+        for round in 2..=6 {
+            state.start_round(0);
+            state.win_round("CT", "Target_Saved");
+            state.end_round(0);
+            assert_eq!(
+                state.teams[1].loss_streak,
+                round.min(economy::MAX_LOSS_STREAK)
+            );
+        }
+        state.start_round(0);
+        state.win_round("CT", "Target_Saved");
+        state.end_round(0);
+        assert_eq!(
+            money(&state, "x"),
+            800 + 1400 + 1900 + 2400 + 2900 + 3400 + 3400 + 3400
+        );
+
+        // ...but only comes down one at a time
+        state.start_round(0);
+        state.win_round("TERRORIST", "Target_Bombed");
+        state.end_round(0);
+        assert_eq!(state.teams[1].loss_streak, 4);
+        assert_eq!(state.teams[0].loss_streak, 1);
+
+        // Nobody can have more than the maximum
+        assert_eq!(money(&state, "a"), 20000);
+
+        // Everyone starts the second half with the starting money
+        state.begin_halftime();
+        assert_eq!(money(&state, "a"), economy::START_MONEY);
+        assert_eq!(money(&state, "x"), economy::START_MONEY);
+        assert_eq!(state.teams[0].loss_streak, 0);
+    }
+
+    #[test]
+    fn test_multi_kills() {
+        let mut round = Round::new(1, 0, ids(&["a", "b"]), ids(&["x", "y", "z"]));
+
+        round.add_kill(kill(0, "a", "x"), 5);
+        round.add_kill(kill(0, "a", "y"), 5);
+        round.add_kill(kill(0, "z", "b"), 5);
+
+        // Team kills don't make a multi-kill
+        round.add_kill(
+            Kill {
+                team_kill: true,
+                ..kill(0, "z", "y")
+            },
+            5,
+        );
+
+        assert_eq!(round.multi_kills(), vec![("a".to_owned(), 2)]);
+    }
+
+    #[test]
+    fn test_clutch_scores() {
+        let mut state = live_game(&["a", "b"], &["x"]);
+        let clutches = |state: &GameState, id: &str| {
+            let score = score(state, id);
+            (score.clutches, score.clutches_won)
+        };
+
+        // b is left 1v1, and wins
+        state.start_round(0);
+        state.player_died("a", 10);
+        state
+            .current_round_mut()
+            .unwrap()
+            .set_result("CT", None, "CTs_Win", 1, 0);
+        state.end_round(20);
+        assert_eq!(clutches(&state, "b"), (1, 1));
+        assert_eq!(clutches(&state, "a"), (0, 0));
+
+        // Then again, and loses
+        state.start_round(30);
+        state.player_died("a", 40);
+        state
+            .current_round_mut()
+            .unwrap()
+            .set_result("TERRORIST", None, "Target_Bombed", 1, 1);
+        state.end_round(50);
+        assert_eq!(clutches(&state, "b"), (2, 1));
+        assert_eq!(clutches(&state, "x"), (0, 0));
+    }
+
+    #[test]
+    fn test_flash_assists() {
+        let mut state = live_game(&["a", "b"], &["x", "y"]);
+
+        state.start_round(0);
+
+        // Blind for 2.1 seconds, which is rounded up to 3 as the log only has whole seconds
+        state.add_blind(10, player("a"), "x".to_owned(), 2.1, false, 1);
+        assert_eq!(
+            state.flash_assister(&kill(13, "b", "x")),
+            Some("a".to_owned())
+        );
+        assert_eq!(state.flash_assister(&kill(14, "b", "x")), None);
+        assert_eq!(state.flash_assister(&kill(9, "b", "x")), None);
+
+        // Flashing someone and then killing them yourself isn't an assist
+        assert_eq!(state.flash_assister(&kill(11, "a", "x")), None);
+
+        // Nor is a team flash, even if the enemy kills the flashed player
+        state.add_blind(20, player("b"), "a".to_owned(), 3.0, true, 2);
+        state.add_blind(20, player("b"), "b".to_owned(), 1.5, true, 2);
+        assert_eq!(state.flash_assister(&kill(21, "x", "a")), None);
+        assert_eq!(state.flash_assister(&kill(21, "y", "b")), None);
+
+        state.record_kill(player("b"), player("x"), kill(12, "b", "x"));
+        assert_eq!(score(&state, "a").flash_assists, 1);
+
+        let a = score(&state, "a");
+        assert_eq!((a.enemies_flashed, a.enemy_blind_time), (1, 2.1));
+        let b = score(&state, "b");
+        assert_eq!((b.team_flashes, b.team_blind_time), (2, 4.5));
+
+        // Blinds from the previous round don't count
+        state.add_blind(30, player("a"), "y".to_owned(), 5.0, false, 3);
+        state.end_round(31);
+        state.start_round(32);
+        assert_eq!(state.flash_assister(&kill(33, "b", "y")), None);
+
+        // Nor do those from before a restart, even though the round number starts again
+        state.reset();
+        state.add_blind(40, player("a"), "y".to_owned(), 5.0, false, 4);
+        state.reset();
+        assert_eq!(state.flash_assister(&kill(41, "b", "y")), None);
+    }
+
+    #[test]
+    fn test_unused_utility() {
+        let mut state = live_game(&["a"], &["x"]);
+
+        // Dying before the first round doesn't count
+        state.left_buyzone("a", &["weapon_knife", "weapon_hegrenade"]);
+        state.player_died("a", 0);
+        assert!(state.scores.is_empty());
+
+        state.start_round(0);
+        state.left_buyzone(
+            "a",
+            &[
+                "weapon_knife",
+                "weapon_flashbang",
+                "weapon_flashbang",
+                "weapon_smokegrenade",
+                "weapon_molotov",
+            ],
+        );
+        state.throw_grenade(player("a"), "flashbang");
+        state.throw_grenade(player("a"), "incgrenade");
+        state.player_died("a", 10);
+
+        let a = score(&state, "a");
+        assert_eq!(a.unused_utility(), (2, 0));
+        assert_eq!(a.grenades_thrown()["flashbang"], 1);
+        assert_eq!(a.grenades_thrown()["incgrenade"], 1);
+
+        // Surviving with a grenade counts at the end of the round instead
+        state.left_buyzone("x", &["weapon_knife_t", "weapon_decoy"]);
+        state.end_round(20);
+        let x = score(&state, "x");
+        assert_eq!(x.unused_utility(), (0, 1));
+
+        // Dying once the round is over doesn't count it twice
+        state.player_died("x", 25);
+        let x = score(&state, "x");
+        assert_eq!(x.unused_utility(), (0, 1));
+    }
+
+    #[test]
+    fn test_time_to_kill() {
+        let mut state = live_game(&["a", "b"], &["x"]);
+
+        // a hits x, but b gets the kill
+        state.start_round(0);
+        state.record_hit(player("a"), "x", "ak47", "chest", 27, 10);
+        state.record_kill(player("b"), player("x"), kill(12, "b", "x"));
+        state.player_died("x", 12);
+
+        // x respawns (as in deathmatch) and a hits and kills them
+        state.record_hit(player("a"), "x", "ak47", "head", 100, 30);
+        state.record_kill(player("a"), player("x"), kill(31, "a", "x"));
+        state.player_died("x", 31);
+
+        let a = score(&state, "a");
+        assert_eq!(a.weapons["ak47"].time_to_kill(), 1.0);
+        assert_eq!(a.time_to_kill, 1.0);
+        assert!(state.first_hits.is_empty());
+    }
+
+    #[test]
+    fn test_suicides() {
+        assert_eq!(SuicideCause::from_weapon("world"), SuicideCause::Fall);
+        assert_eq!(SuicideCause::from_weapon("worldspawn"), SuicideCause::World);
+        assert_eq!(
+            SuicideCause::from_weapon("trigger_hurt"),
+            SuicideCause::World
+        );
+        for grenade in ["hegrenade", "inferno", "molotov", "incgrenade"].iter() {
+            assert_eq!(SuicideCause::from_weapon(grenade), SuicideCause::Grenade);
+        }
+        assert_eq!(SuicideCause::from_weapon("knife"), SuicideCause::Other);
+
+        let mut state = live_game(&["a"], &["x"]);
+        state.start_round(0);
+        let cause = state.add_suicide(
+            10,
+            player("a"),
+            "-320 -2014 273".to_owned(),
+            "hegrenade".to_owned(),
+        );
+        assert_eq!(cause, SuicideCause::Grenade);
+        state.add_suicide(20, player("x"), "1 2 3".to_owned(), "world".to_owned());
+
+        let suicide = &state.suicides[0];
+        assert_eq!((suicide.round, suicide.epoch), (1, 10));
+        assert_eq!(suicide.player, "a");
+        assert_eq!(suicide.position, "-320 -2014 273");
+        assert_eq!(suicide.weapon, "hegrenade");
+        assert_eq!(state.suicides[1].cause(), SuicideCause::Fall);
+
+        // Killing yourself is still a death
+        assert_eq!(
+            (score(&state, "a").deaths, score(&state, "x").deaths),
+            (1, 1)
+        );
+    }
+
+    #[test]
+    fn test_blinds() {
+        let mut state = live_game(&["a", "b"], &["x"]);
+        state.start_round(0);
+        state.add_blind(10, player("a"), "x".to_owned(), 3.68, false, 478);
+        state.add_blind(11, player("a"), "b".to_owned(), 1.2, true, 479);
+
+        let blind = &state.blinds[0];
+        assert_eq!((blind.round, blind.epoch), (1, 10));
+        assert_eq!((blind.attacker.as_str(), blind.victim.as_str()), ("a", "x"));
+        assert_eq!(blind.duration, 3.68);
+        assert!(!blind.team_flash);
+        assert_eq!(blind.entindex, 478);
+
+        let blind = &state.blinds[1];
+        assert!(blind.team_flash);
+        assert_eq!(blind.entindex, 479);
+
+        let a = score(&state, "a");
+        assert_eq!((a.enemies_flashed, a.team_flashes), (1, 1));
+    }
+
+    #[test]
+    fn test_game_modes() {
+        assert_eq!(GameMode::from_name("competitive"), GameMode::Competitive);
+        assert_eq!(GameMode::from_name("scrimcomp2v2"), GameMode::Wingman);
+        assert_eq!(
+            GameMode::from_name("gungameprogressive"),
+            GameMode::ArmsRace
+        );
+        assert_eq!(GameMode::from_name("gungametrbomb"), GameMode::Demolition);
+        assert_eq!(
+            GameMode::from_name("retakes"),
+            GameMode::Other("retakes".to_owned())
+        );
+        assert_eq!(GameMode::from_name("retakes").name(), "retakes");
+
+        assert_eq!(GameMode::from_cvars("0", "0"), GameMode::Casual);
+        assert_eq!(GameMode::from_cvars("0", "1"), GameMode::Competitive);
+        assert_eq!(GameMode::from_cvars("0", "2"), GameMode::Wingman);
+        assert_eq!(GameMode::from_cvars("1", "0"), GameMode::ArmsRace);
+        assert_eq!(GameMode::from_cvars("1", "1"), GameMode::Demolition);
+        assert_eq!(GameMode::from_cvars("1", "2"), GameMode::Deathmatch);
+        assert_eq!(
+            GameMode::from_cvars("6", "0"),
+            GameMode::Other("6/0".to_owned())
+        );
+
+        assert!(GameMode::Competitive.has_winning_team());
+        assert!(!GameMode::Deathmatch.has_winning_team());
+        assert!(!GameMode::ArmsRace.has_winning_team());
+    }
+
+    #[test]
+    fn test_map_groups() {
+        assert_eq!(MapGroup::from_name("<allmaps>"), MapGroup::AllMaps);
+        assert_eq!(MapGroup::from_name(""), MapGroup::AllMaps);
+        assert_eq!(MapGroup::from_name("mg_active"), MapGroup::Active);
+        assert_eq!(MapGroup::from_name("mg_reserves"), MapGroup::Reserves);
+        assert_eq!(MapGroup::from_name("mg_hostage"), MapGroup::Hostage);
+        assert_eq!(
+            MapGroup::from_name("mg_custom"),
+            MapGroup::Other("mg_custom".to_owned())
+        );
+        assert_eq!(MapGroup::from_name("mg_custom").name(), "mg_custom");
+    }
+
+    #[test]
+    fn test_rename() {
+        let id = "STEAM_1:0:00000005";
+        let mut state = GameState::new();
+        assert_eq!(state.see_player(id, id, "3", "James", 10), None);
+        state.add_player_to_team(Player::new("James".to_owned(), id.to_owned(), false), "CT");
+
+        // Seeing them again under the same name changes nothing
+        assert_eq!(state.see_player(id, id, "3", "James", 20), None);
+        assert_eq!(
+            state.see_player(id, id, "3", "Jim", 30),
+            Some("James".to_owned())
+        );
+
+        assert_eq!(state.players().len(), 1);
+        let names: Vec<_> = state.players()[id].names().collect();
+        assert_eq!(names, vec![("James", 10), ("Jim", 30)]);
+
+        let roster = state.cts().players();
+        assert_eq!(roster.len(), 1);
+        assert_eq!((roster[0].name(), roster[0].id()), ("Jim", id));
+        assert!(state.ts().players().is_empty());
     }
 }
@@ -1,4 +1,5 @@
 use super::config;
+use chrono::TimeZone;
 use digest::Digest;
 use md5::Md5;
 use sha1::Sha1;
@@ -49,13 +50,13 @@ pub fn translate_steam_id(steam_id: &str, config: &config::Config) -> String {
             if translation.active {
                 match (&translation.hash, &translation.mappings) {
                     (None, None) => steam_id.to_owned(),
-                    (None, Some(mappings)) => match map_steam_id(steam_id, &mappings) {
+                    (None, Some(mappings)) => match map_steam_id(steam_id, mappings) {
                         Some(mapped_name) => mapped_name,
                         None => steam_id.to_owned(),
                     },
                     (Some(algo), None) => hash_steam_id(steam_id, algo),
                     // If a mapping AND a hash are specified, prefer the mapping and only hash if no mapping fits
-                    (Some(algo), Some(mappings)) => match map_steam_id(steam_id, &mappings) {
+                    (Some(algo), Some(mappings)) => match map_steam_id(steam_id, mappings) {
                         Some(mapped_name) => mapped_name,
                         None => hash_steam_id(steam_id, algo),
                     },
@@ -68,10 +69,74 @@ pub fn translate_steam_id(steam_id: &str, config: &config::Config) -> String {
     }
 }
 
-pub fn timestamp_to_epoch(timestamp: &str) -> i64 {
-    chrono::NaiveDateTime::parse_from_str(timestamp, "%m/%d/%Y - %H:%M:%S")
-        .unwrap()
-        .timestamp()
+/// Bots don't have a Steam ID, so give them a synthetic one based on their name, which stays the same for as long as the bot does
+pub fn bot_id(bot_name: &str) -> String {
+    format!("BOT_{}", bot_name)
+}
+
+/// Is this the Steam ID field of a bot?
+pub fn is_bot(steam_id: &str) -> bool {
+    steam_id == "BOT"
+}
+
+/// Get the ID to use for a player, translating their Steam ID if necessary, or giving them a synthetic ID if they're a bot
+pub fn player_id(player_name: &str, steam_id: &str, config: &config::Config) -> String {
+    if is_bot(steam_id) {
+        bot_id(player_name)
+    } else {
+        translate_steam_id(steam_id, config)
+    }
+}
+
+/// Converts the local timestamps in the server logs (e.g. `05/04/2020 - 21:49:01`) into date/times, using the timezone the server logs in
+///
+/// When the clocks go back, an hour's worth of local timestamps is repeated, so the last date/time seen is used to work out which of the two is meant.
+/// When the clocks go forward, an hour's worth of local timestamps is skipped, so any that do appear are treated as if the clocks had already changed.
+pub struct Clock {
+    timezone: chrono_tz::Tz,
+    latest: Option<chrono::DateTime<chrono_tz::Tz>>,
+}
+
+impl Clock {
+    pub fn new(timezone: chrono_tz::Tz) -> Clock {
+        Clock {
+            timezone,
+            latest: None,
+        }
+    }
+
+    /// Convert a log timestamp into a date/time in the server's timezone, or `None` if it isn't a valid timestamp
+    pub fn datetime(&mut self, timestamp: &str) -> Option<chrono::DateTime<chrono_tz::Tz>> {
+        let naive = chrono::NaiveDateTime::parse_from_str(timestamp, "%m/%d/%Y - %H:%M:%S").ok()?;
+
+        let datetime = match self.timezone.from_local_datetime(&naive) {
+            chrono::LocalResult::Single(datetime) => datetime,
+            // The clocks have gone back, so this local time happened twice - if we've already seen a time later than the first occurrence, this must be the second
+            chrono::LocalResult::Ambiguous(earliest, latest) => match &self.latest {
+                Some(previous) if previous > &earliest => latest,
+                _ => earliest,
+            },
+            // The clocks have gone forward, so this local time never happened - go by the offset from before the change
+            chrono::LocalResult::None => {
+                let hour = chrono::Duration::hours(1);
+                self.timezone
+                    .from_local_datetime(&(naive - hour))
+                    .earliest()
+                    .map(|datetime| datetime + hour)
+                    .unwrap_or_else(|| self.timezone.from_utc_datetime(&naive))
+            }
+        };
+
+        self.latest = Some(datetime);
+
+        Some(datetime)
+    }
+
+    /// The most recent date/time seen in the logs, for lines that don't have a timestamp of their own
+    pub fn latest(&self) -> chrono::DateTime<chrono_tz::Tz> {
+        self.latest
+            .unwrap_or_else(|| self.timezone.timestamp_opt(0, 0).unwrap())
+    }
 }
 
 #[cfg(test)]
@@ -80,9 +145,95 @@ mod tests {
 
     #[test]
     fn test_timestamp_to_epoch() {
-        assert_eq!(timestamp_to_epoch("01/01/1970 - 00:00:00"), 0);
-        assert_eq!(timestamp_to_epoch("05/04/2020 - 21:49:01"), 1588628941);
-        assert_eq!(timestamp_to_epoch("03/29/2021 - 15:39:30"), 1617032370);
+        let mut clock = Clock::new(chrono_tz::UTC);
+        assert_eq!(
+            clock.datetime("01/01/1970 - 00:00:00").unwrap().timestamp(),
+            0
+        );
+        assert_eq!(
+            clock.datetime("05/04/2020 - 21:49:01").unwrap().timestamp(),
+            1588628941
+        );
+        assert_eq!(
+            clock.datetime("03/29/2021 - 15:39:30").unwrap().timestamp(),
+            1617032370
+        );
+    }
+
+    #[test]
+    fn test_timezones() {
+        let mut clock = Clock::new(chrono_tz::Europe::London);
+
+        // British Summer Time
+        let datetime = clock.datetime("05/04/2020 - 21:49:01").unwrap();
+        assert_eq!(datetime.timestamp(), 1588625341);
+        assert_eq!(datetime.to_rfc3339(), "2020-05-04T21:49:01+01:00");
+
+        // Greenwich Mean Time
+        let datetime = clock.datetime("12/04/2020 - 21:49:01").unwrap();
+        assert_eq!(datetime.to_rfc3339(), "2020-12-04T21:49:01+00:00");
+    }
+
+    #[test]
+    fn test_dst_transitions() {
+        let mut clock = Clock::new(chrono_tz::Europe::London);
+
+        // The clocks went back at 02:00 BST on 25/10/2020, so 01:00 to 01:59 happened twice
+        assert_eq!(
+            clock
+                .datetime("10/25/2020 - 01:30:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2020-10-25T01:30:00+01:00"
+        );
+        assert_eq!(
+            clock
+                .datetime("10/25/2020 - 01:59:59")
+                .unwrap()
+                .to_rfc3339(),
+            "2020-10-25T01:59:59+01:00"
+        );
+        assert_eq!(
+            clock
+                .datetime("10/25/2020 - 01:00:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2020-10-25T01:00:00+00:00"
+        );
+        assert_eq!(
+            clock
+                .datetime("10/25/2020 - 01:30:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2020-10-25T01:30:00+00:00"
+        );
+        assert_eq!(
+            clock
+                .datetime("10/25/2020 - 02:00:00")
+                .unwrap()
+                .to_rfc3339(),
+            "2020-10-25T02:00:00+00:00"
+        );
+
+        // The clocks went forward at 01:00 GMT on 29/03/2020, so 01:00 to 01:59 never happened
+        let mut clock = Clock::new(chrono_tz::Europe::London);
+        let before = clock.datetime("03/29/2020 - 00:59:59").unwrap();
+        let during = clock.datetime("03/29/2020 - 01:30:00").unwrap();
+        let after = clock.datetime("03/29/2020 - 02:00:00").unwrap();
+        assert_eq!(during.to_rfc3339(), "2020-03-29T02:30:00+01:00");
+        assert!(before < after);
+    }
+
+    #[test]
+    fn test_bad_timestamps() {
+        let mut clock = Clock::new(chrono_tz::UTC);
+        clock.datetime("05/04/2020 - 21:49:01").unwrap();
+
+        assert!(clock.datetime("13/45/2020 - 21:49:01").is_none());
+        assert!(clock.datetime("triggered").is_none());
+
+        // A bad timestamp doesn't move the clock on
+        assert_eq!(clock.latest().timestamp(), 1588628941);
     }
 
     #[test]
@@ -98,6 +249,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bot_ids() {
+        let mut config = config::Config::default();
+        config.steam_id_translation = Some(config::SteamIdTranslation {
+            active: true,
+            hash: Some(config::HashAlgo::MD5),
+            mappings: None,
+        });
+
+        // Bots are never hashed, and keep the same ID regardless of their userid
+        assert_eq!(player_id("Brett", "BOT", &config), "BOT_Brett");
+        assert_eq!(
+            player_id("Alice", "STEAM_1:1:12345678", &config),
+            "6eb8da106687919a50b73f9fc6e097ac"
+        );
+    }
+
     #[test]
     fn test_steam_id_translation() {
         let mut config = config::Config::default();